@@ -1,5 +1,7 @@
 #[path = "src/i18n_bundle.rs"]
 mod i18n_bundle;
+#[path = "src/number_bundle.rs"]
+mod number_bundle;
 
 use std::env;
 use std::fs;
@@ -9,11 +11,15 @@ use std::path::Path;
 // 1) Read assets/i18n/*.json
 // 2) Pack canonical CBOR bundle
 // 3) Emit OUT_DIR constants included by src/i18n.rs
+// The number format table (assets/number_formats.json) goes through the same
+// steps and is included by src/helpers/number.rs.
 fn main() {
     let i18n_dir = Path::new("assets/i18n");
+    let number_formats = Path::new("assets/number_formats.json");
     let cargo_toml = Path::new("Cargo.toml");
     let component_manifest = Path::new("component.manifest.json");
     println!("cargo:rerun-if-changed={}", i18n_dir.display());
+    println!("cargo:rerun-if-changed={}", number_formats.display());
     println!("cargo:rerun-if-changed={}", cargo_toml.display());
     println!("cargo:rerun-if-changed={}", component_manifest.display());
 
@@ -34,6 +40,20 @@ fn main() {
         "pub const I18N_BUNDLE_CBOR: &[u8] = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/i18n.bundle.cbor\"));\n",
     )
     .expect("write i18n_bundle.rs");
+
+    let formats = number_bundle::load_number_formats(number_formats)
+        .unwrap_or_else(|err| panic!("failed to load number formats: {err}"));
+    let formats = number_bundle::pack_number_formats_to_cbor(&formats)
+        .unwrap_or_else(|err| panic!("failed to pack number formats: {err}"));
+    let formats_path = Path::new(&out_dir).join("number_formats.cbor");
+    fs::write(&formats_path, formats).expect("write number_formats.cbor");
+
+    let rs_path = Path::new(&out_dir).join("number_bundle.rs");
+    fs::write(
+        &rs_path,
+        "pub const NUMBER_FORMATS_CBOR: &[u8] = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/number_formats.cbor\"));\n",
+    )
+    .expect("write number_bundle.rs");
 }
 
 fn sync_manifest_version(manifest_path: &Path) {
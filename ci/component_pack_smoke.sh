@@ -49,7 +49,7 @@ cat > "${TMP_DIR}/payload.json" <<'JSON'
 {
   "config": {
     "templates": {
-      "text": "helo: {{entry.seed}}",
+      "text": "helo: {{payload.seed}}",
       "output_path": "text",
       "wrap": true
     }
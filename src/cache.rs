@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use greentic_types::cbor::canonical;
+use handlebars::{Handlebars, Template};
+use regex::Regex;
+use serde::Serialize;
+
+// Compiled templates kept for the process lifetime. Hot flows invoke the same
+// node many times, so parsing once per distinct source pays off quickly.
+pub const TEMPLATE_CACHE_CAPACITY: usize = 128;
+
+// Regex helper patterns are template literals, so a handful of distinct
+// patterns cover every node in practice.
+pub const REGEX_CACHE_CAPACITY: usize = 64;
+
+// Prepared registries (helpers plus one node's partials and layouts) keyed by
+// mode and partial content; nodes sharing partials share an entry.
+pub const REGISTRY_CACHE_CAPACITY: usize = 32;
+
+static TEMPLATE_CACHE: OnceLock<Mutex<LruCache<CachedTemplate>>> = OnceLock::new();
+static REGEX_CACHE: OnceLock<Mutex<LruCache<Regex>>> = OnceLock::new();
+static REGISTRY_CACHE: OnceLock<Mutex<LruCache<Handlebars<'static>>>> = OnceLock::new();
+
+struct CachedTemplate {
+    source: String,
+    template: Arc<Template>,
+}
+
+// Stable content hash used as cache key and stored alongside configs:
+// `blake3:<first 128 bits, hex>`.
+pub fn template_hash(source: &str) -> String {
+    let digest = canonical::blake3_128(source.as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("blake3:{hex}")
+}
+
+// Returns the compiled template for `source`, compiling and caching on miss.
+// Compile failures are not cached; the caller reports them every time.
+pub fn compiled_template<E>(
+    source: &str,
+    compile: impl FnOnce(&str) -> Result<Template, E>,
+) -> Result<Arc<Template>, E> {
+    compiled_template_keyed(&template_hash(source), source, compile)
+}
+
+// Same as `compiled_template` with a precomputed key, e.g. the
+// `templates.template_hash` written by apply-answers. Entries remember their
+// source, so a stale key only costs a recompile, never a wrong template.
+pub fn compiled_template_keyed<E>(
+    key: &str,
+    source: &str,
+    compile: impl FnOnce(&str) -> Result<Template, E>,
+) -> Result<Arc<Template>, E> {
+    if let Some(entry) = lock(templates()).get(key)
+        && entry.source == source
+    {
+        return Ok(Arc::clone(&entry.template));
+    }
+    let template = Arc::new(compile(source)?);
+    let entry = CachedTemplate {
+        source: source.to_string(),
+        template: Arc::clone(&template),
+    };
+    lock(templates()).insert(key.to_string(), Arc::new(entry));
+    Ok(template)
+}
+
+pub fn template_cache_stats() -> CacheStats {
+    lock(templates()).stats()
+}
+
+// Returns the compiled regex for `pattern`, keyed by the pattern itself.
+// As with templates, compile failures are not cached.
+pub fn compiled_regex<E>(
+    pattern: &str,
+    compile: impl FnOnce(&str) -> Result<Regex, E>,
+) -> Result<Arc<Regex>, E> {
+    if let Some(regex) = lock(regexes()).get(pattern) {
+        return Ok(regex);
+    }
+    let regex = Arc::new(compile(pattern)?);
+    lock(regexes()).insert(pattern.to_string(), Arc::clone(&regex));
+    Ok(regex)
+}
+
+pub fn regex_cache_stats() -> CacheStats {
+    lock(regexes()).stats()
+}
+
+// Returns the registry stored under `key`, building and caching it on miss.
+// Build failures (e.g. a partial that does not parse) are not cached.
+pub fn prepared_registry<E>(
+    key: &str,
+    build: impl FnOnce() -> Result<Handlebars<'static>, E>,
+) -> Result<Arc<Handlebars<'static>>, E> {
+    if let Some(registry) = lock(registries()).get(key) {
+        return Ok(registry);
+    }
+    let registry = Arc::new(build()?);
+    lock(registries()).insert(key.to_string(), Arc::clone(&registry));
+    Ok(registry)
+}
+
+pub fn registry_cache_stats() -> CacheStats {
+    lock(registries()).stats()
+}
+
+fn templates() -> &'static Mutex<LruCache<CachedTemplate>> {
+    TEMPLATE_CACHE.get_or_init(|| Mutex::new(LruCache::new(TEMPLATE_CACHE_CAPACITY)))
+}
+
+fn regexes() -> &'static Mutex<LruCache<Regex>> {
+    REGEX_CACHE.get_or_init(|| Mutex::new(LruCache::new(REGEX_CACHE_CAPACITY)))
+}
+
+fn registries() -> &'static Mutex<LruCache<Handlebars<'static>>> {
+    REGISTRY_CACHE.get_or_init(|| Mutex::new(LruCache::new(REGISTRY_CACHE_CAPACITY)))
+}
+
+// The component is single-threaded on wasm; a poisoned lock only means an
+// earlier panic, and the cache contents are still usable.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub size: usize,
+    pub capacity: usize,
+}
+
+// Small LRU keyed by content hash. Capacity is low enough that a linear scan
+// for the least recently used entry is cheaper than maintaining a list.
+pub struct LruCache<V> {
+    entries: HashMap<String, (Arc<V>, u64)>,
+    capacity: usize,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<V> LruCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity: capacity.max(1),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<Arc<V>> {
+        self.tick += 1;
+        match self.entries.get_mut(key) {
+            Some((value, last_used)) => {
+                *last_used = self.tick;
+                self.hits += 1;
+                Some(Arc::clone(value))
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: String, value: Arc<V>) {
+        self.tick += 1;
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+                self.evictions += 1;
+            }
+        }
+        self.entries.insert(key, (value, self.tick));
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            size: self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_evicts_least_recently_used_entry() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a".to_string(), Arc::new(1));
+        cache.insert("b".to_string(), Arc::new(2));
+        assert_eq!(cache.get("a").as_deref(), Some(&1));
+        cache.insert("c".to_string(), Arc::new(3));
+
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.get("a").as_deref(), Some(&1));
+        assert_eq!(cache.get("c").as_deref(), Some(&3));
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 3,
+                misses: 1,
+                evictions: 1,
+                size: 2,
+                capacity: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn template_hash_is_stable_and_prefixed() {
+        let hash = template_hash("Hello {{name}}");
+        assert_eq!(hash, template_hash("Hello {{name}}"));
+        assert_ne!(hash, template_hash("Hello {{ name }}"));
+        assert!(hash.starts_with("blake3:"));
+        assert_eq!(hash.len(), "blake3:".len() + 32);
+    }
+
+    #[test]
+    fn regexes_compile_once_per_pattern() {
+        let pattern = r"cache-test-(\d+)";
+        let first = compiled_regex(pattern, Regex::new).expect("compile");
+        let second = compiled_regex(pattern, |_| Err("not recompiled")).expect("cached");
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(compiled_regex("(", Regex::new).is_err());
+        assert!(compiled_regex("(", |_| Err("failures are not cached")).is_err());
+    }
+
+    #[test]
+    fn stale_keys_recompile_instead_of_returning_another_template() {
+        let compile = |source: &str| Template::compile(source);
+        let key = "blake3:stale-key-test";
+        let first = compiled_template_keyed(key, "first", compile).expect("compile");
+        let second = compiled_template_keyed(key, "second", compile).expect("compile");
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(
+            format!("{:?}", second.elements),
+            format!(
+                "{:?}",
+                Template::compile("second").expect("compile").elements
+            )
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+use crate::i18n;
+use crate::layouts;
+
+pub const DEFAULT_OUTPUT_PATH: &str = "text";
+pub const DEFAULT_WRAP: bool = true;
+pub const DEFAULT_ROUTE: &str = "out";
+// Port taken by the error output, so flows can branch on failures.
+pub const ERROR_ROUTE: &str = "error";
+pub const DEFAULT_STRICT: bool = true;
+
+// Top-level output keys the runtime writes next to the rendered text, so
+// `output_path` cannot start with them.
+pub const RESERVED_OUTPUT_ROOTS: &[&str] = &["control", "warnings", "debug", "error"];
+
+// Typed view over the node `templates` block (schemas/component.schema.json).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TemplatesConfig {
+    pub text: String,
+    // Per-locale variants of `text`: locale -> template source, picked by
+    // `text_for`.
+    #[serde(default)]
+    pub text_i18n: BTreeMap<String, String>,
+    #[serde(default = "default_output_path")]
+    pub output_path: String,
+    // `false` emits the rendered string itself instead of an object envelope.
+    #[serde(default = "default_wrap")]
+    pub wrap: bool,
+    // Port used when no entry in `routes` matches.
+    #[serde(default)]
+    pub routing: Option<String>,
+    // Conditional ports, evaluated in order; the first truthy `when` wins.
+    #[serde(default)]
+    pub routes: Vec<RouteConfig>,
+    // Fail on unresolved variables; `false` renders them empty and reports
+    // them as warnings instead.
+    #[serde(default = "default_strict")]
+    pub strict: bool,
+    // Adds a `debug` block (template hash, cache counters) to wrapped output.
+    #[serde(default)]
+    pub debug: bool,
+    // Hash of `text` recorded by apply-answers once the template parsed; the
+    // runtime uses it as the compiled-template cache key.
+    #[serde(default)]
+    pub template_hash: Option<String>,
+    // Tenant message catalogs for `{{t}}`: locale -> key -> message. Entries
+    // win over the embedded bundle for the same locale.
+    #[serde(default)]
+    pub messages: BTreeMap<String, BTreeMap<String, String>>,
+    // Named partials for `{{> name}}`: name -> template source.
+    #[serde(default)]
+    pub partials: BTreeMap<String, String>,
+    // Layouts for `{{#> name}}...{{/name}}`: name -> template source. They
+    // replace built-in layouts of the same name.
+    #[serde(default)]
+    pub layouts: BTreeMap<String, String>,
+    // Seed for `uuid`, `random_int`, `pick` and `shuffle`. Setting it, or
+    // `deterministic` (which falls back to `msg.id`), makes them replayable.
+    #[serde(default)]
+    pub seed: Option<String>,
+    #[serde(default)]
+    pub deterministic: bool,
+}
+
+// `when` is a Handlebars expression such as `payload.vip`, or a full template
+// (`{{#if payload.vip}}yes{{/if}}`) whose non-empty output counts as truthy.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RouteConfig {
+    pub when: String,
+    pub to: String,
+}
+
+impl TemplatesConfig {
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        serde_json::from_value(value.clone()).map_err(|err| format!("templates: {err}"))
+    }
+
+    pub fn output_segments(&self) -> Result<Vec<&str>, String> {
+        let segments = path_segments(&self.output_path)?;
+        if RESERVED_OUTPUT_ROOTS.contains(&segments[0]) {
+            return Err(format!(
+                "templates.output_path `{}` starts with `{}`, which the output reserves",
+                self.output_path, segments[0]
+            ));
+        }
+        Ok(segments)
+    }
+
+    pub fn default_route(&self) -> &str {
+        self.routing
+            .as_deref()
+            .filter(|route| !route.trim().is_empty())
+            .unwrap_or(DEFAULT_ROUTE)
+    }
+
+    // Template for a caller with `locales` in order of preference (message
+    // locale, tenant default): the first `text_i18n` variant along each
+    // locale's `i18n::locale_chain` in turn, with the chains' final `en`
+    // tried only after all of them, else `text`. Returns the variant's locale
+    // alongside.
+    pub fn text_for(&self, locales: &[&str]) -> (Option<&str>, &str) {
+        let mut chain = Vec::new();
+        for locale in locales {
+            for candidate in i18n::locale_chain(locale) {
+                if candidate != "en" && !chain.contains(&candidate) {
+                    chain.push(candidate);
+                }
+            }
+        }
+        chain.push("en".to_string());
+        chain
+            .iter()
+            .find_map(|locale| self.text_i18n.get_key_value(locale.as_str()))
+            .map_or((None, self.text.as_str()), |(locale, text)| {
+                (Some(locale.as_str()), text.as_str())
+            })
+    }
+
+    // Everything `{{> name}}` can include: built-in layouts, then config
+    // layouts, then partials, later entries winning.
+    pub fn partial_sources(&self) -> BTreeMap<&str, &str> {
+        let mut sources: BTreeMap<&str, &str> = layouts::BUILTIN.iter().copied().collect();
+        for (name, source) in self.layouts.iter().chain(&self.partials) {
+            sources.insert(name, source);
+        }
+        sources
+    }
+
+    // `deterministic` without a `seed` seeds from the message id; without one
+    // every message would get the same "random" values and uuids.
+    pub fn check_seed(&self, message_id: Option<&JsonValue>) -> Result<(), String> {
+        if self.deterministic && self.seed.is_none() && message_id.is_none_or(JsonValue::is_null) {
+            return Err(
+                "templates.deterministic needs `templates.seed` or a message with an `id`"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    // `wrap: false` emits the bare string, which has nowhere to carry
+    // `control.routing` or lenient-mode `warnings`, so it cannot be combined
+    // with routes, a non-default `routing` or `strict: false`. An empty or
+    // `out` routing is what config flows emit when none was chosen.
+    pub fn check_wrap(&self) -> Result<(), String> {
+        if !self.wrap && (self.default_route() != DEFAULT_ROUTE || !self.routes.is_empty()) {
+            return Err(
+                "templates.wrap `false` emits a bare string without `control.routing`; \
+                 remove `routing` and `routes` or keep `wrap: true`"
+                    .to_string(),
+            );
+        }
+        if !self.wrap && !self.strict {
+            return Err(
+                "templates.wrap `false` emits a bare string without `warnings`; \
+                 keep `strict: true` or `wrap: true`"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn default_output_path() -> String {
+    DEFAULT_OUTPUT_PATH.to_string()
+}
+
+fn default_wrap() -> bool {
+    DEFAULT_WRAP
+}
+
+fn default_strict() -> bool {
+    DEFAULT_STRICT
+}
+
+// Canonical form stored by apply-answers: no BOM, `\n` line endings. Hashes
+// are computed over this form so editors on different platforms agree.
+pub fn normalize_template(text: &str) -> String {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+// Splits a dotted path such as `reply.body.text`, rejecting empty segments.
+pub fn path_segments(path: &str) -> Result<Vec<&str>, String> {
+    let segments: Vec<&str> = path.split('.').collect();
+    if segments.iter().any(|segment| segment.trim().is_empty()) {
+        return Err(format!(
+            "templates.output_path `{path}` has an empty segment"
+        ));
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn output_path_defaults_to_text() {
+        let config = TemplatesConfig::from_value(&json!({ "text": "Hi" })).expect("config");
+        assert_eq!(config.output_path, "text");
+        assert_eq!(config.output_segments().expect("segments"), ["text"]);
+        assert!(config.wrap);
+        assert!(config.strict);
+    }
+
+    #[test]
+    fn default_route_falls_back_to_out() {
+        let config = TemplatesConfig::from_value(&json!({ "text": "Hi", "routing": "done" }))
+            .expect("config");
+        assert_eq!(config.default_route(), "done");
+
+        let bare = TemplatesConfig::from_value(&json!({ "text": "Hi" })).expect("config");
+        assert_eq!(bare.default_route(), "out");
+    }
+
+    #[test]
+    fn unwrapped_output_cannot_route() {
+        let routed = |templates: JsonValue| {
+            TemplatesConfig::from_value(&templates)
+                .expect("config")
+                .check_wrap()
+        };
+        assert!(routed(json!({ "text": "Hi", "wrap": false })).is_ok());
+        assert!(routed(json!({ "text": "Hi", "wrap": false, "routing": "done" })).is_err());
+        assert!(routed(json!({ "text": "Hi", "wrap": false, "routing": "" })).is_ok());
+        assert!(routed(json!({ "text": "Hi", "wrap": false, "routing": "out" })).is_ok());
+        assert!(
+            routed(json!({
+                "text": "Hi",
+                "wrap": false,
+                "routes": [{ "when": "payload.vip", "to": "vip" }]
+            }))
+            .is_err()
+        );
+        assert!(routed(json!({ "text": "Hi", "routing": "done" })).is_ok());
+        assert!(routed(json!({ "text": "Hi", "wrap": false, "strict": false })).is_err());
+        assert!(routed(json!({ "text": "Hi", "strict": false })).is_ok());
+    }
+
+    #[test]
+    fn deterministic_needs_a_seed_or_message_id() {
+        let config =
+            |templates: JsonValue| TemplatesConfig::from_value(&templates).expect("config");
+        let deterministic = config(json!({ "text": "Hi", "deterministic": true }));
+        assert!(deterministic.check_seed(None).is_err());
+        assert!(deterministic.check_seed(Some(&JsonValue::Null)).is_err());
+        assert!(deterministic.check_seed(Some(&json!("m-1"))).is_ok());
+        let seeded = config(json!({ "text": "Hi", "deterministic": true, "seed": "s" }));
+        assert!(seeded.check_seed(None).is_ok());
+        assert!(config(json!({ "text": "Hi" })).check_seed(None).is_ok());
+    }
+
+    #[test]
+    fn text_for_follows_each_locale_chain_before_english() {
+        let config = TemplatesConfig::from_value(&json!({
+            "text": "Hi",
+            "text_i18n": { "en": "Hello", "fr": "Salut", "fr-CA": "Allô", "de": "Hallo" }
+        }))
+        .expect("config");
+        assert_eq!(config.text_for(&["fr-CA"]), (Some("fr-CA"), "Allô"));
+        assert_eq!(config.text_for(&["fr_BE"]), (Some("fr"), "Salut"));
+        assert_eq!(config.text_for(&["nl", "de-AT"]), (Some("de"), "Hallo"));
+        assert_eq!(config.text_for(&["nl"]), (Some("en"), "Hello"));
+
+        let bare = TemplatesConfig::from_value(&json!({ "text": "Hi" })).expect("config");
+        assert_eq!(bare.text_for(&["fr"]), (None, "Hi"));
+    }
+
+    #[test]
+    fn normalizes_bom_and_line_endings() {
+        assert_eq!(
+            normalize_template("\u{feff}Hi\r\n{{payload.name}}\rBye"),
+            "Hi\n{{payload.name}}\nBye"
+        );
+    }
+
+    #[test]
+    fn output_path_cannot_start_with_reserved_keys() {
+        for path in ["control", "warnings.x", "debug", "error.text"] {
+            let config = TemplatesConfig::from_value(&json!({ "text": "Hi", "output_path": path }))
+                .expect("config");
+            assert!(config.output_segments().is_err(), "{path}");
+        }
+        let config =
+            TemplatesConfig::from_value(&json!({ "text": "Hi", "output_path": "reply.control" }))
+                .expect("config");
+        assert_eq!(
+            config.output_segments().expect("segments"),
+            ["reply", "control"]
+        );
+    }
+
+    #[test]
+    fn rejects_empty_path_segments() {
+        assert!(path_segments("reply..text").is_err());
+        assert!(path_segments("").is_err());
+        assert_eq!(
+            path_segments("reply.body.text").expect("segments"),
+            ["reply", "body", "text"]
+        );
+    }
+}
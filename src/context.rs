@@ -0,0 +1,262 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ciborium::value::Value as CborValue;
+use greentic_types::cbor::canonical;
+use serde_json::{Map, Value as JsonValue, json};
+
+// Roots every template can rely on. Payload fields are also exposed at the
+// top level (`{{name}}` == `{{payload.name}}`, even when a helper is called
+// `name`) unless they collide with one of these, in which case the root wins.
+pub const ROOTS: &[&str] = &[
+    "payload", "msg", "state", "config", "tenant", "session", "channel", "meta", "locale",
+];
+
+// Builds the render context for one invocation:
+//
+// - `payload`: the node input payload (`{}` when absent)
+// - `msg`: the channel message envelope as sent by the host
+// - `state`: decoded node state (`null` when the node has none yet)
+// - `config`: the node config, i.e. `{ templates: { ... } }`
+// - `tenant`, `session`, `channel`: shortcuts into `msg`, present only when
+//   the message carries them
+// - `meta`: node/flow metadata (`message_id`, `attempt`, `node_id`,
+//   `flow_id`), each present only when the host supplied it
+// - `locale`: the caller locale (see `caller_locale`), used by helpers that
+//   produce localized text
+pub fn build_context(invocation: &JsonValue, state: &JsonValue) -> JsonValue {
+    let payload = invocation
+        .get("payload")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    let msg = invocation.get("msg").cloned().unwrap_or_else(|| json!({}));
+
+    let mut context = Map::new();
+    if let JsonValue::Object(fields) = &payload {
+        for (name, value) in fields {
+            if !ROOTS.contains(&name.as_str()) {
+                context.insert(name.clone(), value.clone());
+            }
+        }
+    }
+    if let Some(tenant) = msg.get("tenant").filter(|tenant| !tenant.is_null()) {
+        context.insert("tenant".to_string(), tenant.clone());
+    }
+    if let Some(session) = session(&msg) {
+        context.insert("session".to_string(), session);
+    }
+    if let Some(channel) = msg.get("channel").filter(|channel| !channel.is_null()) {
+        context.insert("channel".to_string(), channel.clone());
+    }
+    context.insert("meta".to_string(), meta(invocation, &msg));
+    context.insert("locale".to_string(), json!(caller_locale(invocation)));
+    context.insert("config".to_string(), node_config(invocation));
+    context.insert("state".to_string(), state.clone());
+    context.insert("payload".to_string(), payload);
+    context.insert("msg".to_string(), msg);
+    JsonValue::Object(context)
+}
+
+// Locale used for user-facing messages: an explicit envelope `locale`, then
+// the message locale (`msg.locale` or `msg.metadata.locale`), then the tenant
+// default (`tenant_locale`), else `en`.
+pub fn caller_locale(invocation: &JsonValue) -> String {
+    let msg = invocation.get("msg");
+    [
+        invocation.get("locale"),
+        msg.and_then(|msg| msg.get("locale")),
+        msg.and_then(|msg| msg.get("metadata"))
+            .and_then(|metadata| metadata.get("locale")),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(JsonValue::as_str)
+    .chain(tenant_locale(invocation))
+    .find(|locale| !locale.trim().is_empty())
+    .unwrap_or("en")
+    .to_string()
+}
+
+// The tenant's default locale, `msg.tenant.i18n_id`.
+pub fn tenant_locale(invocation: &JsonValue) -> Option<&str> {
+    invocation
+        .get("msg")
+        .and_then(|msg| msg.get("tenant"))
+        .and_then(|tenant| tenant.get("i18n_id"))
+        .and_then(JsonValue::as_str)
+        .filter(|locale| !locale.trim().is_empty())
+}
+
+// `{ id, user_id }` from `msg.session_id` (or `msg.tenant.session_id`).
+fn session(msg: &JsonValue) -> Option<JsonValue> {
+    let id = msg
+        .get("session_id")
+        .or_else(|| {
+            msg.get("tenant")
+                .and_then(|tenant| tenant.get("session_id"))
+        })
+        .filter(|id| !id.is_null())?;
+    let mut session = json!({ "id": id });
+    if let Some(user_id) = msg.get("user_id").filter(|user_id| !user_id.is_null()) {
+        session["user_id"] = user_id.clone();
+    }
+    Some(session)
+}
+
+// Envelope fields win over values derived from the message, so hosts can
+// override e.g. the attempt counter on retries.
+fn meta(invocation: &JsonValue, msg: &JsonValue) -> JsonValue {
+    let tenant = msg.get("tenant");
+    let mut meta = Map::new();
+    for (name, value) in [
+        (
+            "message_id",
+            invocation.get("message_id").or_else(|| msg.get("id")),
+        ),
+        (
+            "attempt",
+            invocation
+                .get("attempt")
+                .or_else(|| tenant.and_then(|tenant| tenant.get("attempt"))),
+        ),
+        ("node_id", invocation.get("node_id")),
+        ("flow_id", invocation.get("flow_id")),
+    ] {
+        if let Some(value) = value.filter(|value| !value.is_null()) {
+            meta.insert(name.to_string(), value.clone());
+        }
+    }
+    JsonValue::Object(meta)
+}
+
+// Harness invocations nest the node config under `config`; flows send the
+// `templates` block at the top level.
+fn node_config(invocation: &JsonValue) -> JsonValue {
+    if let Some(config) = invocation.get("config").filter(|config| config.is_object()) {
+        return config.clone();
+    }
+    match invocation.get("templates") {
+        Some(templates) => json!({ "templates": templates }),
+        None => json!({}),
+    }
+}
+
+// Decodes a CBOR invocation, payload or state into the JSON the renderer
+// works on. Byte strings become standard base64 text, tags are dropped in
+// favour of their content, non-text map keys are stringified and
+// non-finite floats become `null`, so binary fields never fail a render.
+pub fn from_cbor(bytes: &[u8]) -> Option<JsonValue> {
+    canonical::from_cbor::<CborValue>(bytes)
+        .ok()
+        .map(cbor_to_json)
+}
+
+fn cbor_to_json(value: CborValue) -> JsonValue {
+    match value {
+        CborValue::Null => JsonValue::Null,
+        CborValue::Bool(flag) => json!(flag),
+        CborValue::Integer(integer) => {
+            let integer = i128::from(integer);
+            i64::try_from(integer)
+                .map(|integer| json!(integer))
+                .or_else(|_| u64::try_from(integer).map(|integer| json!(integer)))
+                .unwrap_or_else(|_| json!(integer as f64))
+        }
+        CborValue::Float(float) => json!(float),
+        CborValue::Text(text) => json!(text),
+        CborValue::Bytes(bytes) => json!(STANDARD.encode(bytes)),
+        CborValue::Tag(_, value) => cbor_to_json(*value),
+        CborValue::Array(items) => items.into_iter().map(cbor_to_json).collect(),
+        CborValue::Map(entries) => JsonValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (map_key(key), cbor_to_json(value)))
+                .collect(),
+        ),
+        _ => JsonValue::Null,
+    }
+}
+
+fn map_key(key: CborValue) -> String {
+    match cbor_to_json(key) {
+        JsonValue::String(text) => text,
+        key => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exposes_message_shortcuts_and_metadata() {
+        let invocation = json!({
+            "config": { "templates": { "text": "Hi" } },
+            "msg": {
+                "id": "msg-1",
+                "tenant": { "tenant_id": "default", "session_id": "s-1", "attempt": 2 },
+                "channel": "chat",
+                "user_id": "u-1"
+            },
+            "payload": { "name": "Ada" },
+            "node_id": "greet"
+        });
+        let context = build_context(&invocation, &json!({ "count": 1 }));
+        assert_eq!(context["tenant"]["tenant_id"], "default");
+        assert_eq!(context["session"], json!({ "id": "s-1", "user_id": "u-1" }));
+        assert_eq!(context["channel"], "chat");
+        assert_eq!(
+            context["meta"],
+            json!({ "message_id": "msg-1", "attempt": 2, "node_id": "greet" })
+        );
+        assert_eq!(context["config"]["templates"]["text"], "Hi");
+        assert_eq!(context["state"]["count"], 1);
+    }
+
+    #[test]
+    fn payload_fields_are_bare_identifiers_without_shadowing_roots() {
+        let invocation = json!({
+            "templates": { "text": "Hi" },
+            "msg": { "channel": "chat" },
+            "payload": { "name": "Ada", "channel": "email", "msg": "shadow" }
+        });
+        let context = build_context(&invocation, &JsonValue::Null);
+        assert_eq!(context["name"], "Ada");
+        assert_eq!(context["channel"], "chat");
+        assert_eq!(context["msg"]["channel"], "chat");
+        assert_eq!(context["payload"]["channel"], "email");
+        assert_eq!(context["config"], json!({ "templates": { "text": "Hi" } }));
+    }
+
+    #[test]
+    fn absent_message_fields_stay_absent() {
+        let context = build_context(&json!({ "payload": "plain" }), &JsonValue::Null);
+        assert!(context.get("tenant").is_none());
+        assert!(context.get("session").is_none());
+        assert!(context.get("channel").is_none());
+        assert_eq!(context["meta"], json!({}));
+        assert_eq!(context["locale"], "en");
+        assert_eq!(context["payload"], "plain");
+    }
+
+    #[test]
+    fn cbor_byte_strings_decode_as_base64() {
+        let payload = CborValue::Map(vec![
+            (
+                CborValue::Text("blob".into()),
+                CborValue::Bytes(b"hello".to_vec()),
+            ),
+            (CborValue::Integer(7.into()), CborValue::Float(1.5)),
+            (
+                CborValue::Text("tagged".into()),
+                CborValue::Tag(24, Box::new(CborValue::Bytes(vec![0xff]))),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&payload, &mut bytes).expect("encode cbor");
+        assert_eq!(
+            from_cbor(&bytes),
+            Some(json!({ "blob": "aGVsbG8=", "7": 1.5, "tagged": "/w==" }))
+        );
+        assert_eq!(from_cbor(b"{"), None);
+    }
+}
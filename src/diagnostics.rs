@@ -0,0 +1,607 @@
+use std::collections::BTreeMap;
+
+use handlebars::{RenderErrorReason, TemplateError, TemplateErrorReason};
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue, json};
+
+use crate::helpers::HelperError;
+use crate::i18n;
+
+pub const CODE_TEMPLATE_SYNTAX: &str = "template_syntax";
+pub const CODE_UNKNOWN_HELPER: &str = "unknown_helper";
+pub const CODE_HELPER_ARGUMENTS: &str = "helper_arguments";
+pub const CODE_MISSING_SCOPE: &str = "missing_scope";
+pub const CODE_TEMPLATE_RENDER: &str = "template_render";
+pub const CODE_UNDEFINED_BLOCK: &str = "undefined_block";
+pub const CODE_UNKNOWN_BLOCK: &str = "unknown_block";
+
+// Block helpers that cannot be opened without an argument.
+const ARGUMENT_BLOCKS: &[&str] = &["if", "unless", "each", "with"];
+
+// Byte range into the template source, end exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+// Suggested fix, kept as an i18n key plus arguments so it is localized with
+// the rest of the diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub key: &'static str,
+    pub args: BTreeMap<String, String>,
+}
+
+impl Fix {
+    fn new(key: &'static str, args: &[(&str, &str)]) -> Self {
+        Self {
+            key,
+            args: args
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+}
+
+// A problem located in a template source. Positions are 1-based and count
+// characters, matching what editors show; `span` is in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub span: Option<Span>,
+    pub token: Option<String>,
+    pub snippet: Option<String>,
+    // Untranslated engine message, kept for authors and logs.
+    pub detail: String,
+    pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    fn new(code: &'static str, detail: String) -> Self {
+        Self {
+            code,
+            line: None,
+            column: None,
+            span: None,
+            token: None,
+            snippet: None,
+            detail,
+            fix: None,
+        }
+    }
+
+    // Points the diagnostic at `span`, filling position, token and snippet.
+    fn at(mut self, source: &str, span: Span) -> Self {
+        let (line, column) = position(source, span.start);
+        self.line = Some(line);
+        self.column = Some(column);
+        self.token = Some(source[span.start..span.end].to_string());
+        self.snippet = Some(snippet(source, span));
+        self.span = Some(span);
+        self
+    }
+
+    // Points at `name` as written in the tag at `at`, falling back to its
+    // first use anywhere in the source.
+    fn at_name(self, source: &str, at: Option<usize>, name: &str) -> Self {
+        let span = at
+            .and_then(|at| {
+                let start = at + source[at..].find(name)?;
+                Some(Span {
+                    start,
+                    end: start + name.len(),
+                })
+            })
+            .or_else(|| find_identifier(source, name));
+        match span {
+            Some(span) => self.at(source, span),
+            None => Self {
+                token: Some(name.to_string()),
+                ..self
+            },
+        }
+    }
+
+    fn with_fix(mut self, fix: Option<Fix>) -> Self {
+        self.fix = fix;
+        self
+    }
+
+    pub fn i18n_key(&self) -> String {
+        format!("diagnostics.{}", self.code)
+    }
+
+    // JSON form used by the runtime error envelope and apply-answers errors,
+    // with `message` and `fix.message` resolved for `locale`.
+    pub fn to_json(&self, locale: &str) -> JsonValue {
+        let unknown = || "?".to_string();
+        let args = BTreeMap::from([
+            (
+                "line".to_string(),
+                self.line.map_or_else(unknown, |line| line.to_string()),
+            ),
+            (
+                "column".to_string(),
+                self.column
+                    .map_or_else(unknown, |column| column.to_string()),
+            ),
+            ("token".to_string(), self.token.clone().unwrap_or_default()),
+        ]);
+        let key = self.i18n_key();
+        let mut out = Map::new();
+        out.insert("code".to_string(), json!(self.code));
+        out.insert(
+            "message".to_string(),
+            json!(i18n::t_args(locale, &key, &args)),
+        );
+        out.insert("key".to_string(), json!(key));
+        out.insert("detail".to_string(), json!(self.detail));
+        for (name, value) in [
+            ("line", self.line.map(|line| json!(line))),
+            ("column", self.column.map(|column| json!(column))),
+            ("span", self.span.map(|span| json!(span))),
+            ("token", self.token.as_ref().map(|token| json!(token))),
+            (
+                "snippet",
+                self.snippet.as_ref().map(|snippet| json!(snippet)),
+            ),
+        ] {
+            if let Some(value) = value {
+                out.insert(name.to_string(), value);
+            }
+        }
+        if let Some(fix) = &self.fix {
+            out.insert(
+                "fix".to_string(),
+                json!({
+                    "key": fix.key,
+                    "message": i18n::t_args(locale, fix.key, &fix.args),
+                }),
+            );
+        }
+        JsonValue::Object(out)
+    }
+}
+
+// Diagnoses a parse failure. Handlebars reports where parsing stopped, which
+// for unterminated constructs is the end of the source, so the span is moved
+// to the construct that actually needs fixing.
+pub fn syntax(source: &str, err: &TemplateError) -> Diagnostic {
+    let diagnostic = Diagnostic::new(CODE_TEMPLATE_SYNTAX, err.reason().to_string());
+    let Some(at) = err
+        .pos()
+        .and_then(|(line, column)| offset(source, line, column))
+    else {
+        return diagnostic;
+    };
+
+    if let TemplateErrorReason::MismatchingClosedHelper(opened, closed) = err.reason() {
+        let span = tag_at(source, at).unwrap_or(Span { start: at, end: at });
+        let fix = Fix::new(
+            "diagnostics.fix.rename_closing",
+            &[
+                ("found", &format!("{{{{/{closed}}}}}")),
+                ("expected", &format!("{{{{/{opened}}}}}")),
+            ],
+        );
+        return diagnostic.at(source, span).with_fix(Some(fix));
+    }
+
+    let tags = tags(source);
+    if let Some(tag) = tags.iter().find(|tag| !tag.closed) {
+        let fix = Fix::new("diagnostics.fix.close_expression", &[("tag", "}}")]);
+        return diagnostic.at(source, tag.span).with_fix(Some(fix));
+    }
+    if let Some(tag) = tags
+        .iter()
+        .rev()
+        .find(|tag| tag.span.start <= at && !source[tag.span.start..at].contains('\n'))
+        .filter(|tag| block_without_argument(&source[tag.span.start..tag.span.end]).is_some())
+    {
+        let text = &source[tag.span.start..tag.span.end];
+        let name = block_without_argument(text).unwrap_or_default();
+        let example = format!("{{{{#{name} payload.field}}}}");
+        let fix = Fix::new(
+            "diagnostics.fix.block_argument",
+            &[("tag", text), ("example", &example)],
+        );
+        return diagnostic.at(source, tag.span).with_fix(Some(fix));
+    }
+    if let Some((name, span)) = unclosed_block(source, &tags) {
+        let fix = Fix::new(
+            "diagnostics.fix.close_block",
+            &[("tag", &format!("{{{{/{name}}}}}"))],
+        );
+        return diagnostic.at(source, span).with_fix(Some(fix));
+    }
+
+    let span = tag_at(source, at).unwrap_or_else(|| {
+        let end = source[at..]
+            .char_indices()
+            .nth(1)
+            .map_or(source.len(), |(index, _)| at + index);
+        Span { start: at, end }
+    });
+    let fix = Fix::new("diagnostics.fix.check_expression", &[]);
+    diagnostic.at(source, span).with_fix(Some(fix))
+}
+
+// Diagnoses a render failure other than a missing variable. Unknown helpers
+// get a did-you-mean suggestion drawn from `helpers`.
+pub fn render_failure(source: &str, err: &handlebars::RenderError, helpers: &[&str]) -> Diagnostic {
+    let at = err
+        .line_no
+        .zip(err.column_no)
+        .and_then(|(line, column)| offset(source, line, column));
+
+    if let RenderErrorReason::HelperNotFound(name) = err.reason() {
+        let fix = did_you_mean(name, helpers).map(|suggestion| {
+            Fix::new(
+                "diagnostics.fix.did_you_mean",
+                &[("suggestion", suggestion)],
+            )
+        });
+        return Diagnostic::new(CODE_UNKNOWN_HELPER, err.reason().to_string())
+            .at_name(source, at, name)
+            .with_fix(fix);
+    }
+    if let RenderErrorReason::NestedError(nested) = err.reason()
+        && let Some(helper_err) = nested.downcast_ref::<HelperError>()
+    {
+        let fix = Fix::new(
+            "diagnostics.fix.helper_usage",
+            &[("usage", helper_err.usage)],
+        );
+        return Diagnostic::new(CODE_HELPER_ARGUMENTS, helper_err.to_string())
+            .at_name(source, at, helper_err.helper)
+            .with_fix(Some(fix));
+    }
+    // The position may be inside a layout or partial rather than `source`,
+    // so only the name is searched for.
+    if let RenderErrorReason::PartialNotFound(name) = err.reason() {
+        return Diagnostic::new(CODE_UNDEFINED_BLOCK, err.reason().to_string())
+            .at_name(source, None, name)
+            .with_fix(Some(define_block(name)));
+    }
+
+    let diagnostic = Diagnostic::new(CODE_TEMPLATE_RENDER, err.reason().to_string());
+    match at.and_then(|at| tag_at(source, at)) {
+        Some(span) => diagnostic.at(source, span),
+        None => diagnostic,
+    }
+}
+
+// Diagnoses a variable the strict renderer could not resolve, pointing at its
+// first use in the source when it can be found there.
+pub fn missing_scope(source: &str, path: &str) -> Diagnostic {
+    let diagnostic = Diagnostic::new(
+        CODE_MISSING_SCOPE,
+        format!("variable `{path}` is not defined"),
+    )
+    .with_fix(Some(Fix::new(
+        "diagnostics.fix.provide_value",
+        &[("path", path)],
+    )));
+    match find_identifier(source, path) {
+        Some(span) => diagnostic.at(source, span),
+        None => Diagnostic {
+            token: Some(path.to_string()),
+            ..diagnostic
+        },
+    }
+}
+
+// A required block of `layout` that the `{{#> layout}}` use in `source`
+// leaves undefined. Points at the layout tag; the token is the block.
+pub fn undefined_block(source: &str, layout: &str, block: &str) -> Diagnostic {
+    let diagnostic = Diagnostic::new(
+        CODE_UNDEFINED_BLOCK,
+        format!("layout `{layout}` needs block `{block}`, which is not defined"),
+    )
+    .at_name(source, None, layout)
+    .with_fix(Some(define_block(block)));
+    Diagnostic {
+        token: Some(block.to_string()),
+        ..diagnostic
+    }
+}
+
+// An `{{#*inline}}` override inside `{{#> layout}}` that the layout never
+// renders, usually a misspelt block name.
+pub fn unknown_block(source: &str, layout: &str, block: &str, declared: &[&str]) -> Diagnostic {
+    let fix = match did_you_mean(block, declared) {
+        Some(suggestion) => Fix::new(
+            "diagnostics.fix.did_you_mean",
+            &[("suggestion", suggestion)],
+        ),
+        None => Fix::new(
+            "diagnostics.fix.layout_blocks",
+            &[("layout", layout), ("blocks", &declared.join(", "))],
+        ),
+    };
+    Diagnostic::new(
+        CODE_UNKNOWN_BLOCK,
+        format!("layout `{layout}` has no block `{block}`"),
+    )
+    .at_name(source, None, block)
+    .with_fix(Some(fix))
+}
+
+fn define_block(name: &str) -> Fix {
+    Fix::new(
+        "diagnostics.fix.define_block",
+        &[(
+            "example",
+            &format!("{{{{#*inline \"{name}\"}}}}...{{{{/inline}}}}"),
+        )],
+    )
+}
+
+// Closest candidate within a small edit distance, ignoring case.
+pub fn did_you_mean<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let limit = (name.chars().count() / 3).clamp(1, 3);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// A `{{ ... }}` tag in the source; `closed` is false when no `}}` follows.
+struct Tag {
+    span: Span,
+    closed: bool,
+}
+
+fn tags(source: &str) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    let mut rest = 0;
+    while let Some(found) = source[rest..].find("{{") {
+        let start = rest + found;
+        match source[start + 2..].find("}}") {
+            Some(close) => {
+                let mut end = start + 2 + close + 2;
+                // Swallow the extra brace of `{{{ }}}` triple-stash tags.
+                if source[start..].starts_with("{{{") && source[end..].starts_with('}') {
+                    end += 1;
+                }
+                tags.push(Tag {
+                    span: Span { start, end },
+                    closed: true,
+                });
+                rest = end;
+            }
+            None => {
+                let end = source[start..]
+                    .find('\n')
+                    .map_or(source.len(), |newline| start + newline);
+                tags.push(Tag {
+                    span: Span { start, end },
+                    closed: false,
+                });
+                break;
+            }
+        }
+    }
+    tags
+}
+
+fn tag_at(source: &str, at: usize) -> Option<Span> {
+    tags(source)
+        .into_iter()
+        .find(|tag| tag.span.start <= at && at < tag.span.end)
+        .map(|tag| tag.span)
+}
+
+// `{{#if}}` style openers: returns the helper name when no argument follows.
+fn block_without_argument(tag: &str) -> Option<&str> {
+    let inner = tag.strip_prefix("{{")?.strip_suffix("}}")?;
+    let name = inner.trim_matches('~').trim().strip_prefix('#')?.trim();
+    ARGUMENT_BLOCKS.contains(&name).then_some(name)
+}
+
+// Name of a block tag (`{{#name`, `{{^name`, `{{#> name`, `{{#*inline`) and
+// whether it opens or closes.
+fn block_name(tag: &str) -> Option<(bool, &str)> {
+    let inner = tag.strip_prefix("{{")?.trim_start_matches('~').trim_start();
+    let (opens, rest) = match inner.chars().next()? {
+        '#' | '^' => (true, &inner[1..]),
+        '/' => (false, &inner[1..]),
+        _ => return None,
+    };
+    let rest = rest.trim_start_matches(['>', '*']).trim_start();
+    let end = rest
+        .find(|ch: char| ch.is_whitespace() || ch == '}' || ch == '~')
+        .unwrap_or(rest.len());
+    let name = &rest[..end];
+    (!name.is_empty() && name != "else").then_some((opens, name))
+}
+
+fn unclosed_block<'a>(source: &'a str, tags: &[Tag]) -> Option<(&'a str, Span)> {
+    let mut open: Vec<(&str, Span)> = Vec::new();
+    for tag in tags {
+        match block_name(&source[tag.span.start..tag.span.end]) {
+            Some((true, name)) => open.push((name, tag.span)),
+            Some((false, name)) => {
+                if let Some(index) = open.iter().rposition(|(opened, _)| *opened == name) {
+                    open.truncate(index);
+                }
+            }
+            None => {}
+        }
+    }
+    open.pop()
+}
+
+// First whole-word occurrence of `ident` inside a tag.
+fn find_identifier(source: &str, ident: &str) -> Option<Span> {
+    let is_word = |ch: char| ch.is_alphanumeric() || matches!(ch, '_' | '.' | '@' | '-');
+    tags(source).into_iter().find_map(|tag| {
+        let text = &source[tag.span.start..tag.span.end];
+        text.match_indices(ident).find_map(|(index, _)| {
+            let before = text[..index].chars().next_back();
+            let after = text[index + ident.len()..].chars().next();
+            (!before.is_some_and(is_word) && !after.is_some_and(is_word)).then(|| {
+                let start = tag.span.start + index;
+                Span {
+                    start,
+                    end: start + ident.len(),
+                }
+            })
+        })
+    })
+}
+
+// Byte offset of a 1-based line/column pair; a column one past the end of the
+// line is allowed since parsers report positions there.
+fn offset(source: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = if line <= 1 {
+        0
+    } else {
+        source.match_indices('\n').nth(line - 2)?.0 + 1
+    };
+    let line_end = source[line_start..]
+        .find('\n')
+        .map_or(source.len(), |index| line_start + index);
+    let text = &source[line_start..line_end];
+    let index = text
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map_or(text.len(), |(index, _)| index);
+    Some(line_start + index)
+}
+
+fn position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, source[line_start..offset].chars().count() + 1)
+}
+
+// The line containing `span.start` with a caret underline:
+//
+//   2 | Hi {{shout name}}
+//     |       ^^^^^
+fn snippet(source: &str, span: Span) -> String {
+    let (line, column) = position(source, span.start);
+    let line_start = source[..span.start]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line_end = source[line_start..]
+        .find('\n')
+        .map_or(source.len(), |index| line_start + index);
+    let underline = source[span.start..span.end.min(line_end).max(span.start)]
+        .chars()
+        .count()
+        .max(1);
+    let gutter = " ".repeat(line.to_string().len());
+    format!(
+        "{line} | {}\n{gutter} | {}{}",
+        &source[line_start..line_end],
+        " ".repeat(column - 1),
+        "^".repeat(underline)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use handlebars::Template;
+
+    fn parse_error(source: &str) -> Diagnostic {
+        syntax(
+            source,
+            &Template::compile(source).expect_err("syntax error"),
+        )
+    }
+
+    #[test]
+    fn mismatched_closing_tags_point_at_the_closer() {
+        let diagnostic = parse_error("Hi\n{{#if payload.vip}}x{{/each}}");
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(2), Some(21)));
+        assert_eq!(diagnostic.token.as_deref(), Some("{{/each}}"));
+        assert_eq!(diagnostic.span, Some(Span { start: 23, end: 32 }));
+        assert_eq!(
+            diagnostic.snippet.as_deref(),
+            Some("2 | {{#if payload.vip}}x{{/each}}\n  |                     ^^^^^^^^^")
+        );
+        let fix = diagnostic.fix.expect("fix");
+        assert_eq!(fix.key, "diagnostics.fix.rename_closing");
+        assert_eq!(fix.args["expected"], "{{/if}}");
+    }
+
+    #[test]
+    fn unterminated_and_unclosed_constructs_point_at_their_opening() {
+        let diagnostic = parse_error("Hello {{payload.name");
+        assert_eq!(diagnostic.token.as_deref(), Some("{{payload.name"));
+        assert_eq!(
+            diagnostic.fix.expect("fix").key,
+            "diagnostics.fix.close_expression"
+        );
+
+        let diagnostic = parse_error("{{#each payload.items}}{{this}}");
+        assert_eq!(diagnostic.token.as_deref(), Some("{{#each payload.items}}"));
+        let fix = diagnostic.fix.expect("fix");
+        assert_eq!(fix.key, "diagnostics.fix.close_block");
+        assert_eq!(fix.args["tag"], "{{/each}}");
+
+        let diagnostic = parse_error("Hello {{#if}}");
+        assert_eq!(diagnostic.column, Some(7));
+        assert_eq!(
+            diagnostic.fix.expect("fix").key,
+            "diagnostics.fix.block_argument"
+        );
+    }
+
+    #[test]
+    fn suggests_close_helper_names() {
+        let helpers = ["if", "each", "upper", "lower", "truncate"];
+        assert_eq!(did_you_mean("uper", &helpers), Some("upper"));
+        assert_eq!(did_you_mean("Truncat", &helpers), Some("truncate"));
+        assert_eq!(did_you_mean("shout", &helpers), None);
+    }
+
+    #[test]
+    fn missing_scope_locates_first_use() {
+        let diagnostic = missing_scope("Hi {{payload.name}}\n{{msg.locale}}", "msg.locale");
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(2), Some(3)));
+        assert_eq!(diagnostic.token.as_deref(), Some("msg.locale"));
+        assert!(
+            missing_scope("{{payload.names}}", "payload.name")
+                .span
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn localizes_message_and_fix() {
+        let diagnostic = parse_error("Hi {{#if a}}x{{/each}}");
+        let json = diagnostic.to_json("en");
+        assert_eq!(json["key"], "diagnostics.template_syntax");
+        assert_eq!(json["message"], "Syntax error at line 1, column 14.");
+        assert_eq!(
+            json["fix"]["message"],
+            "Replace `{{/each}}` with `{{/if}}`."
+        );
+    }
+}
@@ -0,0 +1,569 @@
+use std::cmp::Ordering;
+
+use serde_json::{Value as JsonValue, json};
+
+use super::json::path;
+use super::{Args, HelperError, HelperSpec};
+use crate::i18n;
+
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "length",
+        usage: "{{length array_object_or_text}}",
+        call: length,
+    },
+    HelperSpec {
+        name: "first",
+        usage: "{{first array [count]}}",
+        call: first,
+    },
+    HelperSpec {
+        name: "last",
+        usage: "{{last array [count]}}",
+        call: last,
+    },
+    HelperSpec {
+        name: "slice",
+        usage: "{{slice array_or_text start [end]}}",
+        call: slice,
+    },
+    HelperSpec {
+        name: "sort_by",
+        usage: "{{sort_by array field [\"asc\"|\"desc\"]}}",
+        call: sort_by,
+    },
+    HelperSpec {
+        name: "group_by",
+        usage: "{{group_by array field}}",
+        call: group_by,
+    },
+    HelperSpec {
+        name: "filter_by",
+        usage: "{{filter_by array field value}}",
+        call: filter_by,
+    },
+    HelperSpec {
+        name: "map_field",
+        usage: "{{map_field array field}}",
+        call: map_field,
+    },
+    HelperSpec {
+        name: "unique",
+        usage: "{{unique array [field]}}",
+        call: unique,
+    },
+    HelperSpec {
+        name: "sum",
+        usage: "{{sum array [field]}}",
+        call: sum,
+    },
+    HelperSpec {
+        name: "join",
+        usage: "{{join array [separator] oxford=true conjunction=\"and\"}}",
+        call: join,
+    },
+    HelperSpec {
+        name: "chunk",
+        usage: "{{chunk array size}}",
+        call: chunk,
+    },
+];
+
+const ARRAY: &str = "an array";
+const ORDER: &str = "\"asc\" or \"desc\"";
+const NUMBERS: &str = "an array of numbers";
+const DEFAULT_SEPARATOR: &str = ", ";
+
+// Items of an array, object key count, or characters of a string; `null`
+// (e.g. a missing value in lenient mode) has length 0.
+fn length(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(match args.value(0)? {
+        JsonValue::Null => 0,
+        JsonValue::Array(items) => items.len(),
+        JsonValue::Object(map) => map.len(),
+        JsonValue::String(text) => text.chars().count(),
+        value => return Err(args.type_error(0, "an array, object or string", value)),
+    }))
+}
+
+// The first item, or the first `count` items as an array.
+fn first(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let items = array(args, 0)?;
+    Ok(match args.get(1) {
+        Some(_) => json!(&items[..args.count(1)?.min(items.len())]),
+        None => items.first().cloned().unwrap_or(JsonValue::Null),
+    })
+}
+
+fn last(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let items = array(args, 0)?;
+    Ok(match args.get(1) {
+        Some(_) => json!(&items[items.len().saturating_sub(args.count(1)?)..]),
+        None => items.last().cloned().unwrap_or(JsonValue::Null),
+    })
+}
+
+// `Array.prototype.slice` semantics: negative positions count from the end
+// and out-of-range positions are clamped. Strings slice by character.
+fn slice(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let bounds = |len: usize| -> Result<(usize, usize), HelperError> {
+        let start = position(args.integer(1)?, len);
+        let end = match args.get(2) {
+            Some(_) => position(args.integer(2)?, len),
+            None => len,
+        };
+        Ok((start, end.max(start)))
+    };
+    match args.value(0)? {
+        JsonValue::String(text) => {
+            let (start, end) = bounds(text.chars().count())?;
+            Ok(json!(
+                text.chars()
+                    .skip(start)
+                    .take(end - start)
+                    .collect::<String>()
+            ))
+        }
+        _ => {
+            let items = array(args, 0)?;
+            let (start, end) = bounds(items.len())?;
+            Ok(json!(&items[start..end]))
+        }
+    }
+}
+
+// Stable sort on `field` (a dotted path; `""` sorts by the items themselves).
+// Mixed types order as null < booleans < numbers < strings < arrays <
+// objects, so missing fields come first when ascending.
+fn sort_by(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let mut items = array(args, 0)?.clone();
+    let field = args.text(1)?;
+    let descending = match args.opt_text(2)?.as_deref() {
+        None | Some("asc") => false,
+        Some("desc") => true,
+        Some(_) => return Err(args.type_error(2, ORDER, args.value(2)?)),
+    };
+    items.sort_by(|left, right| {
+        let order = compare(field_of(left, &field), field_of(right, &field));
+        if descending { order.reverse() } else { order }
+    });
+    Ok(JsonValue::Array(items))
+}
+
+// `[{ key, items }]` in order of first appearance, keeping the key's type.
+fn group_by(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let field = args.text(1)?;
+    let mut groups: Vec<(&JsonValue, Vec<JsonValue>)> = Vec::new();
+    for item in array(args, 0)? {
+        let key = field_of(item, &field);
+        match groups.iter_mut().find(|(existing, _)| equal(existing, key)) {
+            Some((_, members)) => members.push(item.clone()),
+            None => groups.push((key, vec![item.clone()])),
+        }
+    }
+    Ok(groups
+        .into_iter()
+        .map(|(key, items)| json!({ "key": key, "items": items }))
+        .collect())
+}
+
+// Items whose `field` equals `value`; numbers compare by value (`1 == 1.0`).
+fn filter_by(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(3, 3)?;
+    let field = args.text(1)?;
+    let wanted = args.value(2)?;
+    Ok(array(args, 0)?
+        .iter()
+        .filter(|item| equal(field_of(item, &field), wanted))
+        .cloned()
+        .collect())
+}
+
+// `field` of every item, `null` where it is missing.
+fn map_field(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let field = args.text(1)?;
+    Ok(array(args, 0)?
+        .iter()
+        .map(|item| field_of(item, &field).clone())
+        .collect())
+}
+
+// First occurrence of each item, or of each `field` value.
+fn unique(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let field = args.opt_text(1)?.unwrap_or_default();
+    let mut seen: Vec<&JsonValue> = Vec::new();
+    let mut out = Vec::new();
+    for item in array(args, 0)? {
+        let key = field_of(item, &field);
+        if !seen.iter().any(|existing| equal(existing, key)) {
+            seen.push(key);
+            out.push(item.clone());
+        }
+    }
+    Ok(JsonValue::Array(out))
+}
+
+// Nulls and missing fields are skipped; numeric strings count. The result
+// stays an integer while every term is one.
+fn sum(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let field = args.opt_text(1)?.unwrap_or_default();
+    let mut integer = Some(0i64);
+    let mut float = 0.0;
+    for item in array(args, 0)? {
+        let term = field_of(item, &field);
+        let (whole, value) = match term {
+            JsonValue::Null => continue,
+            JsonValue::Number(number) => (number.as_i64(), number.as_f64()),
+            JsonValue::String(text) => (
+                text.trim().parse::<i64>().ok(),
+                text.trim().parse::<f64>().ok(),
+            ),
+            _ => (None, None),
+        };
+        let Some(value) = value.filter(|value| value.is_finite()) else {
+            return Err(args.type_error(0, NUMBERS, args.value(0)?));
+        };
+        integer = integer
+            .zip(whole)
+            .and_then(|(total, whole)| total.checked_add(whole));
+        float += value;
+    }
+    Ok(match integer {
+        Some(total) => json!(total),
+        None => json!(float),
+    })
+}
+
+// Joins items as text (nulls skipped, objects and arrays as JSON). With
+// `oxford=true` the last item is joined with the caller-locale "and" (or
+// `conjunction`): "a, b, and c", "a and b".
+fn join(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let separator = args
+        .opt_text(1)?
+        .unwrap_or_else(|| DEFAULT_SEPARATOR.to_string());
+    let parts: Vec<String> = array(args, 0)?
+        .iter()
+        .filter(|item| !item.is_null())
+        .map(|item| match item {
+            JsonValue::String(text) => text.clone(),
+            other => other.to_string(),
+        })
+        .collect();
+    let oxford = args.hash("oxford").is_some_and(truthy);
+    if !oxford || parts.len() < 2 {
+        return Ok(json!(parts.join(&separator)));
+    }
+    let conjunction = match args.hash("conjunction") {
+        Some(JsonValue::String(conjunction)) => conjunction.clone(),
+        _ => i18n::t(args.locale(), "list.and"),
+    };
+    let (head, last) = parts.split_at(parts.len() - 1);
+    let last = &last[0];
+    Ok(json!(if head.len() == 1 {
+        format!("{} {conjunction} {last}", head[0])
+    } else {
+        format!("{}{}{conjunction} {last}", head.join(&separator), separator)
+    }))
+}
+
+// Consecutive arrays of `size` items; the last one may be shorter.
+fn chunk(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let size = args.count(1)?;
+    if size == 0 {
+        return Err(args.type_error(1, "a positive integer", args.value(1)?));
+    }
+    Ok(array(args, 0)?
+        .chunks(size)
+        .map(|chunk| JsonValue::Array(chunk.to_vec()))
+        .collect())
+}
+
+fn array<'a>(args: &Args<'a>, index: usize) -> Result<&'a Vec<JsonValue>, HelperError> {
+    match args.value(index)? {
+        JsonValue::Array(items) => Ok(items),
+        value => Err(args.type_error(index, ARRAY, value)),
+    }
+}
+
+fn field_of<'v>(item: &'v JsonValue, field: &str) -> &'v JsonValue {
+    path(item, field).unwrap_or(&JsonValue::Null)
+}
+
+fn position(index: i64, len: usize) -> usize {
+    if index < 0 {
+        len.saturating_sub(usize::try_from(index.unsigned_abs()).unwrap_or(usize::MAX))
+    } else {
+        usize::try_from(index).unwrap_or(usize::MAX).min(len)
+    }
+}
+
+// Flag hash arguments such as `oxford=true` (also `"true"`).
+pub(super) fn truthy(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Bool(flag) => *flag,
+        JsonValue::String(text) => text == "true",
+        _ => false,
+    }
+}
+
+// JSON equality, except that numbers compare by value (`2 == 2.0`).
+pub(super) fn equal(left: &JsonValue, right: &JsonValue) -> bool {
+    match (left, right) {
+        (JsonValue::Number(left), JsonValue::Number(right)) => left.as_f64() == right.as_f64(),
+        _ => left == right,
+    }
+}
+
+// Total order across JSON types (see `sort_by`); arrays and objects of the
+// same type compare by size.
+pub(super) fn compare(left: &JsonValue, right: &JsonValue) -> Ordering {
+    fn rank(value: &JsonValue) -> u8 {
+        match value {
+            JsonValue::Null => 0,
+            JsonValue::Bool(_) => 1,
+            JsonValue::Number(_) => 2,
+            JsonValue::String(_) => 3,
+            JsonValue::Array(_) => 4,
+            JsonValue::Object(_) => 5,
+        }
+    }
+    match (left, right) {
+        (JsonValue::Bool(left), JsonValue::Bool(right)) => left.cmp(right),
+        (JsonValue::Number(left), JsonValue::Number(right)) => left
+            .as_f64()
+            .unwrap_or_default()
+            .total_cmp(&right.as_f64().unwrap_or_default()),
+        (JsonValue::String(left), JsonValue::String(right)) => left.cmp(right),
+        (JsonValue::Array(left), JsonValue::Array(right)) => left.len().cmp(&right.len()),
+        (JsonValue::Object(left), JsonValue::Object(right)) => left.len().cmp(&right.len()),
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render_in(locale: &str, template: &str) -> String {
+        let context = json!({
+            "locale": locale,
+            "mixed": [3, "b", null, 1.5, true, { "k": 1 }, "a", [1]],
+            "orders": [
+                { "id": 1, "status": "open", "total": 10, "tags": ["a"] },
+                { "id": 2, "status": "paid", "total": 2.5 },
+                { "id": 3, "status": "open", "total": "4" },
+                { "id": 4, "total": null }
+            ],
+            "names": ["Ada", "Grace", "Linus"],
+            "text": "héllo"
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    fn render(template: &str) -> String {
+        render_in("en", template)
+    }
+
+    #[test]
+    fn length() {
+        assert_eq!(render("{{length mixed}}"), "8");
+        assert_eq!(render("{{length orders.0}}"), "4");
+        assert_eq!(render("{{length text}}"), "5");
+        assert_eq!(render("{{#if (length orders)}}some{{/if}}"), "some");
+    }
+
+    #[test]
+    fn first() {
+        assert_eq!(render("{{first mixed}}"), "3");
+        assert_eq!(render("{{{json (first mixed 2)}}}"), r#"[3,"b"]"#);
+        assert_eq!(
+            render("{{{json (first names 9)}}}"),
+            r#"["Ada","Grace","Linus"]"#
+        );
+    }
+
+    #[test]
+    fn last() {
+        assert_eq!(render("{{{json (last mixed)}}}"), "[1]");
+        assert_eq!(render("{{{json (last names 2)}}}"), r#"["Grace","Linus"]"#);
+    }
+
+    #[test]
+    fn slice() {
+        assert_eq!(render("{{{json (slice mixed 1 3)}}}"), r#"["b",null]"#);
+        assert_eq!(
+            render("{{{json (slice names -2)}}}"),
+            r#"["Grace","Linus"]"#
+        );
+        assert_eq!(render("{{{json (slice names 2 1)}}}"), "[]");
+        assert_eq!(render("{{slice text 1 -1}}"), "éll");
+    }
+
+    #[test]
+    fn sort_by() {
+        assert_eq!(
+            render("{{{json (sort_by mixed \"\")}}}"),
+            r#"[null,true,1.5,3,"a","b",[1],{"k":1}]"#
+        );
+        assert_eq!(
+            render("{{#each (sort_by orders \"total\" \"desc\")}}{{id}}{{/each}}"),
+            "3124"
+        );
+        assert_eq!(
+            render("{{#each (sort_by orders \"status\")}}{{id}}{{/each}}"),
+            "4132"
+        );
+    }
+
+    #[test]
+    fn group_by() {
+        assert_eq!(
+            render("{{#each (group_by orders \"status\")}}{{key}}:{{length items}};{{/each}}"),
+            "open:2;paid:1;:1;"
+        );
+    }
+
+    #[test]
+    fn filter_by() {
+        assert_eq!(
+            render("{{#each (filter_by orders \"status\" \"open\")}}{{id}}{{/each}}"),
+            "13"
+        );
+        assert_eq!(
+            render("{{{json (filter_by orders \"total\" 2.5)}}}"),
+            r#"[{"id":2,"status":"paid","total":2.5}]"#
+        );
+        assert_eq!(render("{{length (filter_by mixed \"\" 3.0)}}"), "1");
+    }
+
+    #[test]
+    fn map_field() {
+        assert_eq!(
+            render("{{{json (map_field orders \"status\")}}}"),
+            r#"["open","paid","open",null]"#
+        );
+        assert_eq!(
+            render("{{{json (map_field orders \"tags.0\")}}}"),
+            r#"["a",null,null,null]"#
+        );
+    }
+
+    #[test]
+    fn unique() {
+        assert_eq!(
+            render("{{{json (unique (map_field orders \"status\"))}}}"),
+            r#"["open","paid",null]"#
+        );
+        assert_eq!(
+            render("{{#each (unique orders \"status\")}}{{id}}{{/each}}"),
+            "124"
+        );
+    }
+
+    #[test]
+    fn sum() {
+        assert_eq!(render("{{sum (map_field orders \"id\")}}"), "10");
+        assert_eq!(render("{{sum orders \"total\"}}"), "16.5");
+        assert_eq!(render("{{sum (slice orders 0 1) \"total\"}}"), "10");
+    }
+
+    #[test]
+    fn join() {
+        assert_eq!(render("{{join names}}"), "Ada, Grace, Linus");
+        assert_eq!(render("{{join names \" / \"}}"), "Ada / Grace / Linus");
+        assert_eq!(
+            render("{{join names oxford=true}}"),
+            "Ada, Grace, and Linus"
+        );
+        assert_eq!(
+            render("{{join (first names 2) oxford=true}}"),
+            "Ada and Grace"
+        );
+        assert_eq!(
+            render_in("fr", "{{join names oxford=true conjunction=\"ou\"}}"),
+            "Ada, Grace, ou Linus"
+        );
+        assert_eq!(
+            render_in("fr", "{{join (first names 2) oxford=true}}"),
+            "Ada et Grace"
+        );
+        assert_eq!(
+            render("{{{join (slice mixed 0 6) \"|\"}}}"),
+            r#"3|b|1.5|true|{"k":1}"#
+        );
+    }
+
+    #[test]
+    fn chunk() {
+        assert_eq!(
+            render("{{#each (chunk names 2)}}[{{join this}}]{{/each}}"),
+            "[Ada, Grace][Linus]"
+        );
+    }
+
+    #[test]
+    fn bare_names_prefer_context_fields_over_helpers() {
+        let context = json!({ "first": "Ada", "length": 3, "items": ["x", "y"] });
+        let render = |template| {
+            render_template(template, &context, RenderOptions::default())
+                .expect("render")
+                .text
+        };
+        assert_eq!(render("hi {{first}} {{length}}"), "hi Ada 3");
+        assert_eq!(render("{{first items}} {{length items}}"), "x 2");
+        assert_eq!(
+            render("{{#each items}}{{@index}}{{/each}} {{#with this}}{{first}}{{/with}}"),
+            "01 Ada"
+        );
+    }
+
+    #[test]
+    fn invalid_arguments_are_helper_errors() {
+        for (template, detail) in [
+            (
+                "{{first text}}",
+                "`first` argument 1 must be an array, got a string",
+            ),
+            (
+                "{{sum mixed}}",
+                "`sum` argument 1 must be an array of numbers, got an array",
+            ),
+            (
+                "{{chunk names 0}}",
+                "`chunk` argument 2 must be a positive integer, got a number",
+            ),
+            (
+                "{{sort_by names \"\" \"up\"}}",
+                "`sort_by` argument 3 must be \"asc\" or \"desc\", got a string",
+            ),
+            (
+                "{{slice names \"x\"}}",
+                "`slice` argument 2 must be an integer, got a string",
+            ),
+        ] {
+            assert_eq!(
+                helper_error_detail(
+                    template,
+                    &json!({ "text": "abc", "names": ["a"], "mixed": [1, "x"] })
+                ),
+                detail
+            );
+        }
+    }
+}
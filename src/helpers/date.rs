@@ -0,0 +1,511 @@
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::time::SystemTime;
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat, Timelike,
+    Utc,
+};
+use serde_json::{Value as JsonValue, json};
+
+use super::{Args, HelperError, HelperSpec};
+use crate::i18n;
+
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "now",
+        usage: "{{now [pattern] [offset]}}",
+        call: now_helper,
+    },
+    HelperSpec {
+        name: "format_date",
+        usage: "{{format_date date pattern [offset]}}",
+        call: format_date,
+    },
+    HelperSpec {
+        name: "parse_date",
+        usage: "{{parse_date text [pattern]}}",
+        call: parse_date,
+    },
+    HelperSpec {
+        name: "add_duration",
+        usage: "{{add_duration date duration}}",
+        call: add_duration,
+    },
+    HelperSpec {
+        name: "date_diff",
+        usage: "{{date_diff from to [unit]}}",
+        call: date_diff,
+    },
+    HelperSpec {
+        name: "to_timezone",
+        usage: "{{to_timezone date offset}}",
+        call: to_timezone,
+    },
+];
+
+const DATE: &str = "a date (RFC 3339, YYYY-MM-DD or unix seconds)";
+const OFFSET: &str = "a UTC offset such as +02:00 or Z";
+const DURATION: &str = "a duration such as 1h30m, -2d or PT90M";
+const PATTERN: &str = "a supported strftime pattern";
+const UNIT: &str = "one of seconds, minutes, hours, days, weeks";
+
+thread_local! {
+    static FIXED_NOW: Cell<Option<DateTime<Utc>>> = const { Cell::new(None) };
+}
+
+// Pins the clock read by `now` on the current thread so golden tests render
+// stable timestamps; `None` goes back to the WASI wall clock.
+pub fn set_fixed_now(now: Option<DateTime<Utc>>) {
+    FIXED_NOW.with(|fixed| fixed.set(now));
+}
+
+pub fn now() -> DateTime<Utc> {
+    FIXED_NOW
+        .with(Cell::get)
+        .unwrap_or_else(|| DateTime::<Utc>::from(SystemTime::now()))
+}
+
+// RFC 3339 timestamp in UTC, or formatted with `pattern` in `offset`.
+fn now_helper(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(0, 2)?;
+    let now = now().fixed_offset();
+    let Some(pattern) = args.opt_text(0)? else {
+        return Ok(json!(rfc3339(&now)));
+    };
+    let now = shift(args, now, 1)?;
+    Ok(json!(format(args, &now, &pattern, 0)?))
+}
+
+fn format_date(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let date = shift(args, date(args, 0)?, 2)?;
+    Ok(json!(format(args, &date, &args.text(1)?, 1)?))
+}
+
+// Normalizes a date to RFC 3339. `pattern` uses chrono's strftime syntax and
+// may omit the time (midnight) and offset (UTC).
+fn parse_date(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let Some(pattern) = args.opt_text(1)? else {
+        return Ok(json!(rfc3339(&date(args, 0)?)));
+    };
+    let text = args.text(0)?;
+    let parsed = DateTime::parse_from_str(&text, &pattern)
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(&text, &pattern)
+                .ok()
+                .map(|naive| naive.and_utc().fixed_offset())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(&text, &pattern)
+                .ok()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+                .map(|naive| naive.and_utc().fixed_offset())
+        });
+    match parsed {
+        Some(date) => Ok(json!(rfc3339(&date))),
+        None => Err(args.type_error(0, "a date matching the pattern", &json!(text))),
+    }
+}
+
+fn add_duration(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let date = date(args, 0)?;
+    let duration = duration(args, 1)?;
+    let shifted = date
+        .checked_add_signed(duration)
+        .ok_or_else(|| args.type_error(1, DURATION, args.get(1).unwrap_or(&JsonValue::Null)))?;
+    Ok(json!(rfc3339(&shifted)))
+}
+
+// `to - from`, truncated to whole units (seconds by default).
+fn date_diff(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let delta = date(args, 1)? - date(args, 0)?;
+    let unit = args.opt_text(2)?.unwrap_or_else(|| "seconds".to_string());
+    let value = match unit.trim_end_matches('s') {
+        "second" => delta.num_seconds(),
+        "minute" => delta.num_minutes(),
+        "hour" => delta.num_hours(),
+        "day" => delta.num_days(),
+        "week" => delta.num_weeks(),
+        _ => return Err(args.type_error(2, UNIT, &json!(unit))),
+    };
+    Ok(json!(value))
+}
+
+fn to_timezone(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let date = shift(args, date(args, 0)?, 1)?;
+    Ok(json!(rfc3339(&date)))
+}
+
+fn rfc3339(date: &DateTime<FixedOffset>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+// Dates are RFC 3339 strings, bare `YYYY-MM-DD` / `YYYY-MM-DDTHH:MM:SS`
+// (UTC), unix seconds, or the literal `now`.
+fn date(args: &Args<'_>, index: usize) -> Result<DateTime<FixedOffset>, HelperError> {
+    let value = args.get(index).unwrap_or(&JsonValue::Null);
+    let parsed = match value {
+        JsonValue::Number(number) => number
+            .as_i64()
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+            .map(|date| date.fixed_offset()),
+        JsonValue::String(text) if text == "now" => Some(now().fixed_offset()),
+        JsonValue::String(text) => DateTime::parse_from_rfc3339(text)
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S")
+                    .ok()
+                    .map(|naive| naive.and_utc().fixed_offset())
+            })
+            .or_else(|| {
+                NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|day| day.and_hms_opt(0, 0, 0))
+                    .map(|naive| naive.and_utc().fixed_offset())
+            }),
+        _ => None,
+    };
+    parsed.ok_or_else(|| args.type_error(index, DATE, value))
+}
+
+// Moves `date` into the offset at `index`, when that argument is present.
+fn shift(
+    args: &Args<'_>,
+    date: DateTime<FixedOffset>,
+    index: usize,
+) -> Result<DateTime<FixedOffset>, HelperError> {
+    let Some(text) = args.opt_text(index)? else {
+        return Ok(date);
+    };
+    let offset = parse_offset(&text).ok_or_else(|| args.type_error(index, OFFSET, &json!(text)))?;
+    Ok(date.with_timezone(&offset))
+}
+
+// `Z`, `UTC`, `+HH`, `+HHMM` or `+HH:MM`.
+fn parse_offset(text: &str) -> Option<FixedOffset> {
+    let text = text.trim();
+    if matches!(text, "Z" | "z" | "UTC" | "utc") {
+        return FixedOffset::east_opt(0);
+    }
+    let sign = match text.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits: String = text[1..].chars().filter(|ch| *ch != ':').collect();
+    if !digits.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+// Durations are seconds (number), compact units (`1w2d3h4m5s`, optionally
+// signed) or ISO 8601 (`P1DT2H`, `PT90M`).
+fn duration(args: &Args<'_>, index: usize) -> Result<Duration, HelperError> {
+    let value = args.get(index).unwrap_or(&JsonValue::Null);
+    let parsed = match value {
+        JsonValue::Number(number) => number.as_i64().and_then(Duration::try_seconds),
+        JsonValue::String(text) => parse_duration(text),
+        _ => None,
+    };
+    parsed.ok_or_else(|| args.type_error(index, DURATION, value))
+}
+
+fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (iso, body) = match text.strip_prefix('P') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    let mut in_time = !iso;
+    let mut any = false;
+    for ch in body.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+        if iso && ch == 'T' && number.is_empty() {
+            in_time = true;
+            continue;
+        }
+        let amount: i64 = number.parse().ok()?;
+        number.clear();
+        // In ISO form weeks/days precede `T` and h/m/s follow it, so a date
+        // part `M` (months) is rejected rather than read as minutes.
+        let unit = match ch.to_ascii_lowercase() {
+            'w' | 'd' if iso && in_time => return None,
+            'h' | 'm' | 's' if !in_time => return None,
+            'w' => 7 * 86_400,
+            'd' => 86_400,
+            'h' => 3_600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(amount.checked_mul(unit)?)?;
+        any = true;
+    }
+    if !number.is_empty() || !any {
+        return None;
+    }
+    Duration::try_seconds(sign * seconds)
+}
+
+// strftime subset. Names (`%B %b %A %a %p`) come from the i18n bundle for
+// the caller locale; `-` after `%` drops zero padding (`%-d`).
+fn format(
+    args: &Args<'_>,
+    date: &DateTime<FixedOffset>,
+    pattern: &str,
+    pattern_index: usize,
+) -> Result<String, HelperError> {
+    let locale = args.locale();
+    let name = |kind: &str, index: u32| i18n::t(locale, &format!("date.{kind}.{index}"));
+    let unsupported = || args.type_error(pattern_index, PATTERN, &json!(pattern));
+
+    let mut out = String::with_capacity(pattern.len() + 16);
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            out.push(ch);
+            continue;
+        }
+        let mut spec = chars.next().ok_or_else(unsupported)?;
+        let pad = if spec == '-' {
+            spec = chars.next().ok_or_else(unsupported)?;
+            false
+        } else {
+            true
+        };
+        let number = |out: &mut String, value: u32, width: usize| {
+            if pad {
+                let _ = write!(out, "{value:0width$}");
+            } else {
+                let _ = write!(out, "{value}");
+            }
+        };
+        match spec {
+            'Y' => {
+                let _ = write!(out, "{}", date.year());
+            }
+            'y' => number(&mut out, date.year().rem_euclid(100) as u32, 2),
+            'm' => number(&mut out, date.month(), 2),
+            'd' => number(&mut out, date.day(), 2),
+            'e' => {
+                let _ = write!(out, "{:>2}", date.day());
+            }
+            'j' => number(&mut out, date.ordinal(), 3),
+            'H' => number(&mut out, date.hour(), 2),
+            'I' => number(&mut out, date.hour12().1, 2),
+            'M' => number(&mut out, date.minute(), 2),
+            'S' => number(&mut out, date.second(), 2),
+            'f' => {
+                let _ = write!(out, "{:06}", date.nanosecond() / 1_000);
+            }
+            'u' => number(&mut out, date.weekday().number_from_monday(), 1),
+            'w' => number(&mut out, date.weekday().num_days_from_sunday(), 1),
+            'B' => out.push_str(&name("month", date.month())),
+            'b' | 'h' => out.push_str(&name("month_short", date.month())),
+            'A' => out.push_str(&name("weekday", date.weekday().number_from_monday())),
+            'a' => out.push_str(&name("weekday_short", date.weekday().number_from_monday())),
+            'p' => out.push_str(&i18n::t(
+                locale,
+                if date.hour12().0 {
+                    "date.pm"
+                } else {
+                    "date.am"
+                },
+            )),
+            'z' => out.push_str(&date.format("%z").to_string()),
+            ':' => match chars.next() {
+                Some('z') => out.push_str(&date.format("%:z").to_string()),
+                _ => return Err(unsupported()),
+            },
+            'Z' if date.offset().local_minus_utc() == 0 => out.push_str("UTC"),
+            'Z' => out.push_str(&date.format("%:z").to_string()),
+            's' => {
+                let _ = write!(out, "{}", date.timestamp());
+            }
+            'F' => out.push_str(&format(args, date, "%Y-%m-%d", pattern_index)?),
+            'T' => out.push_str(&format(args, date, "%H:%M:%S", pattern_index)?),
+            'R' => out.push_str(&format(args, date, "%H:%M", pattern_index)?),
+            'D' => out.push_str(&format(args, date, "%m/%d/%y", pattern_index)?),
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            '%' => out.push('%'),
+            _ => return Err(unsupported()),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use super::*;
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render_in(locale: &str, template: &str) -> String {
+        set_fixed_now(Utc.with_ymd_and_hms(2024, 3, 5, 14, 7, 9).single());
+        let context = json!({
+            "locale": locale,
+            "when": "2024-12-24T18:30:00+01:00",
+            "day": "2024-02-28",
+            "epoch": 1_700_000_000
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    fn render(template: &str) -> String {
+        render_in("en", template)
+    }
+
+    #[test]
+    fn now() {
+        assert_eq!(render("{{now}}"), "2024-03-05T14:07:09Z");
+        assert_eq!(render("{{now \"%H:%M\" \"+02:00\"}}"), "16:07");
+    }
+
+    #[test]
+    fn format_date() {
+        assert_eq!(
+            render("{{format_date when \"%A %-d %B %Y, %I:%M %p %:z\"}}"),
+            "Tuesday 24 December 2024, 06:30 PM +01:00"
+        );
+        assert_eq!(
+            render("{{format_date epoch \"%F %T %Z\"}}"),
+            "2023-11-14 22:13:20 UTC"
+        );
+        assert_eq!(
+            render("{{format_date when \"%a %b %e %R\" \"Z\"}}"),
+            "Tue Dec 24 17:30"
+        );
+    }
+
+    #[test]
+    fn format_date_localizes_names() {
+        assert_eq!(
+            render_in("fr-FR", "{{format_date when \"%A %-d %B\"}}"),
+            "mardi 24 décembre"
+        );
+    }
+
+    #[test]
+    fn parse_date() {
+        assert_eq!(render("{{parse_date day}}"), "2024-02-28T00:00:00Z");
+        assert_eq!(
+            render("{{parse_date \"05/03/2024 09:15\" \"%d/%m/%Y %H:%M\"}}"),
+            "2024-03-05T09:15:00Z"
+        );
+        assert_eq!(
+            render("{{parse_date \"31.12.2024\" \"%d.%m.%Y\"}}"),
+            "2024-12-31T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn add_duration() {
+        assert_eq!(
+            render("{{add_duration day \"1d12h\"}}"),
+            "2024-02-29T12:00:00Z"
+        );
+        assert_eq!(
+            render("{{add_duration when \"-PT90M\"}}"),
+            "2024-12-24T17:00:00+01:00"
+        );
+        assert_eq!(
+            render("{{add_duration \"now\" 60}}"),
+            "2024-03-05T14:08:09Z"
+        );
+    }
+
+    #[test]
+    fn date_diff() {
+        assert_eq!(render("{{date_diff day \"2024-03-01\" \"days\"}}"), "2");
+        assert_eq!(render("{{date_diff \"2024-03-01\" day \"hours\"}}"), "-48");
+        assert_eq!(render("{{date_diff day \"2024-02-28T00:01:30Z\"}}"), "90");
+    }
+
+    #[test]
+    fn to_timezone() {
+        assert_eq!(
+            render("{{to_timezone when \"-05:00\"}}"),
+            "2024-12-24T12:30:00-05:00"
+        );
+        assert_eq!(
+            render("{{to_timezone when \"UTC\"}}"),
+            "2024-12-24T17:30:00Z"
+        );
+    }
+
+    #[test]
+    fn invalid_arguments_are_helper_errors() {
+        for (template, detail) in [
+            (
+                "{{format_date \"yesterday\" \"%F\"}}",
+                format!("`format_date` argument 1 must be {DATE}, got a string"),
+            ),
+            (
+                "{{format_date day \"%Q\"}}",
+                format!("`format_date` argument 2 must be {PATTERN}, got a string"),
+            ),
+            (
+                "{{to_timezone day \"Europe/Paris\"}}",
+                format!("`to_timezone` argument 2 must be {OFFSET}, got a string"),
+            ),
+            (
+                "{{add_duration day \"soon\"}}",
+                format!("`add_duration` argument 2 must be {DURATION}, got a string"),
+            ),
+            (
+                "{{date_diff day day \"fortnights\"}}",
+                format!("`date_diff` argument 3 must be {UNIT}, got a string"),
+            ),
+        ] {
+            assert_eq!(
+                helper_error_detail(template, &json!({ "day": "2024-02-28" })),
+                detail
+            );
+        }
+    }
+
+    #[test]
+    fn parses_durations_and_offsets() {
+        assert_eq!(parse_duration("1w"), Duration::try_days(7));
+        assert_eq!(parse_duration("P1DT2H"), Duration::try_hours(26));
+        assert_eq!(parse_duration("PT1M"), Duration::try_minutes(1));
+        assert_eq!(parse_duration("P1M"), None);
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_offset("+0530"), FixedOffset::east_opt(19_800));
+        assert_eq!(parse_offset("-03"), FixedOffset::east_opt(-10_800));
+        assert_eq!(parse_offset("+02:75"), None);
+    }
+}
@@ -0,0 +1,291 @@
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use serde_json::{Value as JsonValue, json};
+use sha2::{Digest, Sha256};
+
+use super::collection::truthy;
+use super::{Args, HelperError, HelperSpec};
+
+// Encoders take text (numbers and booleans as written, `null` as empty).
+// CBOR byte strings reach templates as standard base64 text, so
+// `{{base64_decode payload.blob}}` round-trips them when they hold UTF-8.
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "url_encode",
+        usage: "{{url_encode text}}",
+        call: url_encode,
+    },
+    HelperSpec {
+        name: "url_decode",
+        usage: "{{url_decode text}}",
+        call: url_decode,
+    },
+    HelperSpec {
+        name: "base64_encode",
+        usage: "{{base64_encode text url=false}}",
+        call: base64_encode,
+    },
+    HelperSpec {
+        name: "base64_decode",
+        usage: "{{base64_decode text}}",
+        call: base64_decode,
+    },
+    HelperSpec {
+        name: "html_escape",
+        usage: "{{{html_escape text}}}",
+        call: html_escape,
+    },
+    HelperSpec {
+        name: "markdown_escape",
+        usage: "{{markdown_escape text}}",
+        call: markdown_escape,
+    },
+    HelperSpec {
+        name: "json_escape",
+        usage: "{{{json_escape text}}}",
+        call: json_escape,
+    },
+    HelperSpec {
+        name: "sha256",
+        usage: "{{sha256 text}}",
+        call: sha256,
+    },
+    HelperSpec {
+        name: "blake3",
+        usage: "{{blake3 text}}",
+        call: blake3,
+    },
+];
+
+const PERCENT_ENCODED: &str = "percent-encoded UTF-8 text";
+const BASE64: &str = "base64-encoded UTF-8 text";
+const MARKDOWN_SPECIAL: &str = "\\`*_{}[]()<>#+-.!|~";
+
+// RFC 3986 unreserved characters pass through; every other UTF-8 byte is
+// `%XX`, so the result is safe in a path segment or query value.
+fn url_encode(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let text = args.text(0)?;
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    Ok(json!(encoded))
+}
+
+// Also turns `+` into a space, as form-encoded query strings use it.
+fn url_decode(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let decoded = percent_decode(&args.text(0)?).ok_or_else(|| {
+        args.type_error(0, PERCENT_ENCODED, args.get(0).unwrap_or(&JsonValue::Null))
+    })?;
+    Ok(json!(decoded))
+}
+
+// Standard alphabet with padding; `url=true` gives the URL-safe alphabet
+// without padding, for tokens in links.
+fn base64_encode(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let text = args.text(0)?;
+    let encoded = if args.hash("url").is_some_and(truthy) {
+        URL_SAFE_NO_PAD.encode(text)
+    } else {
+        STANDARD.encode(text)
+    };
+    Ok(json!(encoded))
+}
+
+// Accepts either alphabet, padded or not.
+fn base64_decode(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let text = args.text(0)?;
+    let text = text.trim();
+    let decoded = [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| engine.decode(text).ok())
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| args.type_error(0, BASE64, args.get(0).unwrap_or(&JsonValue::Null)))?;
+    Ok(json!(decoded))
+}
+
+// The same escaping `{{...}}` applies, for use inside `{{{...}}}` or when
+// building markup in a sub-expression; `{{html_escape x}}` escapes twice.
+fn html_escape(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(handlebars::html_escape(&args.text(0)?)))
+}
+
+// Backslash-escapes the characters CommonMark treats as markup, so user
+// input renders literally in Markdown channels.
+fn markdown_escape(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let text = args.text(0)?;
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if MARKDOWN_SPECIAL.contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    Ok(json!(escaped))
+}
+
+// The body of a JSON string literal, without the surrounding quotes.
+fn json_escape(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let quoted = JsonValue::String(args.text(0)?).to_string();
+    Ok(json!(quoted[1..quoted.len() - 1]))
+}
+
+// Lowercase hex digests of the UTF-8 text.
+fn sha256(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(hex(&Sha256::digest(args.text(0)?.as_bytes()))))
+}
+
+fn blake3(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(
+        hex(blake3::hash(args.text(0)?.as_bytes()).as_bytes())
+    ))
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = std::str::from_utf8(rest.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &rest[2..];
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render(template: &str) -> String {
+        let context = json!({
+            "query": "tea & cake/ü",
+            "note": "<b>\"hi\"</b>",
+            "title": "*bold* [link](x) #1",
+            "quote": "say \"hi\"\n",
+            "blob": "aGVsbG8=",
+            "id": 42
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    #[test]
+    fn url_encode() {
+        assert_eq!(render("{{url_encode query}}"), "tea%20%26%20cake%2F%C3%BC");
+        assert_eq!(render("{{url_encode id}}"), "42");
+    }
+
+    #[test]
+    fn url_decode() {
+        assert_eq!(
+            render("{{url_decode \"tea%20%26+cake%2F%C3%BC\"}}"),
+            "tea &amp; cake/ü"
+        );
+        assert_eq!(
+            render("{{url_decode (url_encode query)}}"),
+            "tea &amp; cake/ü"
+        );
+    }
+
+    #[test]
+    fn base64_encode() {
+        assert_eq!(render("{{base64_encode \"hello?\"}}"), "aGVsbG8/");
+        assert_eq!(render("{{base64_encode \"hello?\" url=true}}"), "aGVsbG8_");
+        assert_eq!(render("{{base64_encode \"hi\"}}"), "aGk&#x3D;");
+    }
+
+    #[test]
+    fn base64_decode() {
+        assert_eq!(render("{{base64_decode blob}}"), "hello");
+        assert_eq!(render("{{base64_decode \"aGVsbG8_\"}}"), "hello?");
+        assert_eq!(render("{{base64_decode \"aGk\"}}"), "hi");
+    }
+
+    #[test]
+    fn html_escape() {
+        assert_eq!(
+            render("{{{html_escape note}}}"),
+            "&lt;b&gt;&quot;hi&quot;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn markdown_escape() {
+        assert_eq!(
+            render("{{{markdown_escape title}}}"),
+            r"\*bold\* \[link\]\(x\) \#1"
+        );
+    }
+
+    #[test]
+    fn json_escape() {
+        assert_eq!(render("{{{json_escape quote}}}"), r#"say \"hi\"\n"#);
+    }
+
+    #[test]
+    fn sha256() {
+        assert_eq!(
+            render("{{sha256 \"abc\"}}"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn blake3() {
+        assert_eq!(
+            render("{{blake3 \"abc\"}}"),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn invalid_arguments_are_helper_errors() {
+        for (template, detail) in [
+            (
+                "{{url_decode \"100%\"}}",
+                "`url_decode` argument 1 must be percent-encoded UTF-8 text, got a string",
+            ),
+            (
+                "{{base64_decode \"not base64!\"}}",
+                "`base64_decode` argument 1 must be base64-encoded UTF-8 text, got a string",
+            ),
+            (
+                "{{sha256 list}}",
+                "`sha256` argument 1 must be a string, got an array",
+            ),
+        ] {
+            assert_eq!(
+                helper_error_detail(template, &json!({ "list": [] })),
+                detail
+            );
+        }
+    }
+}
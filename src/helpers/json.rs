@@ -0,0 +1,313 @@
+use serde_json::{Value as JsonValue, json};
+
+use super::{Args, HelperError, HelperSpec};
+
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "json",
+        usage: "{{json value}}",
+        call: json_compact,
+    },
+    HelperSpec {
+        name: "json_pretty",
+        usage: "{{json_pretty value}}",
+        call: json_pretty,
+    },
+    HelperSpec {
+        name: "get",
+        usage: "{{get value \"items.0.name\" [default]}}",
+        call: get,
+    },
+    HelperSpec {
+        name: "pointer",
+        usage: "{{pointer value \"/items/0/name\" [default]}}",
+        call: pointer,
+    },
+    HelperSpec {
+        name: "keys",
+        usage: "{{keys object_or_array}}",
+        call: keys,
+    },
+    HelperSpec {
+        name: "values",
+        usage: "{{values object_or_array}}",
+        call: values,
+    },
+    HelperSpec {
+        name: "entries",
+        usage: "{{entries object_or_array}}",
+        call: entries,
+    },
+    HelperSpec {
+        name: "has",
+        usage: "{{has object_or_array key_or_pointer}}",
+        call: has,
+    },
+    // Bare `{{payload}}`, `{{msg}}` and `{{state}}`: handlebars checks helpers
+    // before the context for name-only expressions, so these make the roots
+    // render as compact JSON instead of `[object]`. Paths such as
+    // `{{payload.name}}` are plain lookups and unaffected.
+    HelperSpec {
+        name: "payload",
+        usage: "{{payload}}",
+        call: payload,
+    },
+    HelperSpec {
+        name: "msg",
+        usage: "{{msg}}",
+        call: msg,
+    },
+    HelperSpec {
+        name: "state",
+        usage: "{{state}}",
+        call: state,
+    },
+];
+
+const COLLECTION: &str = "an object or array";
+const POINTER: &str = "a JSON Pointer such as /items/0";
+
+fn json_compact(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(args.value(0)?.to_string()))
+}
+
+// Two-space indented, for debug output and code blocks.
+fn json_pretty(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let pretty = serde_json::to_string_pretty(args.value(0)?).unwrap_or_default();
+    Ok(json!(pretty))
+}
+
+// Dotted path (`items.0.name`, `items[0].name`); numeric segments index
+// arrays. Missing or `null` values yield `default`, else `null`. With a
+// `default`, `value` itself may be missing, so optional payload fields never
+// fail a strict render.
+fn get(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let found = path(args.value(0)?, &args.text(1)?);
+    Ok(found_or_default(args, found))
+}
+
+// Dotted-path lookup shared with the collection helpers; an empty path is
+// the value itself.
+pub(super) fn path<'v>(value: &'v JsonValue, path: &str) -> Option<&'v JsonValue> {
+    path.replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, child)
+}
+
+// RFC 6901 pointer (`/items/0/name`, `~1` for `/`, `~0` for `~`).
+fn pointer(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let pointer = args.text(1)?;
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(args.type_error(1, POINTER, args.value(1)?));
+    }
+    Ok(found_or_default(args, args.value(0)?.pointer(&pointer)))
+}
+
+// Object keys (sorted) or array indices.
+fn keys(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(match collection(args)? {
+        JsonValue::Object(map) => json!(map.keys().collect::<Vec<_>>()),
+        JsonValue::Array(items) => json!((0..items.len()).collect::<Vec<_>>()),
+        _ => unreachable!("collection() only returns objects and arrays"),
+    })
+}
+
+fn values(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(match collection(args)? {
+        JsonValue::Object(map) => json!(map.values().collect::<Vec<_>>()),
+        items => items.clone(),
+    })
+}
+
+// `[{ key, value }]`, for iterating objects with `#each` while keeping both.
+fn entries(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(match collection(args)? {
+        JsonValue::Object(map) => map
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": value }))
+            .collect(),
+        JsonValue::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, value)| json!({ "key": index, "value": value }))
+            .collect(),
+        _ => unreachable!("collection() only returns objects and arrays"),
+    })
+}
+
+// Object key or array index; a leading `/` makes it a JSON Pointer. Any
+// other value simply has nothing.
+fn has(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let value = args.value(0)?;
+    let key = args.text(1)?;
+    let found = if key.starts_with('/') {
+        value.pointer(&key).is_some()
+    } else {
+        child(value, &key).is_some()
+    };
+    Ok(json!(found))
+}
+
+fn payload(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    root_json(args, "payload")
+}
+
+fn msg(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    root_json(args, "msg")
+}
+
+fn state(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    root_json(args, "state")
+}
+
+// Scalars render as themselves; objects and arrays as compact JSON.
+fn root_json(args: &Args<'_>, name: &str) -> Result<JsonValue, HelperError> {
+    args.arity(0, 0)?;
+    Ok(match args.root(name) {
+        Some(value @ (JsonValue::Object(_) | JsonValue::Array(_))) => json!(value.to_string()),
+        Some(value) => value.clone(),
+        None => JsonValue::Null,
+    })
+}
+
+fn child<'v>(value: &'v JsonValue, segment: &str) -> Option<&'v JsonValue> {
+    match value {
+        JsonValue::Object(map) => map.get(segment),
+        JsonValue::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+fn collection<'a>(args: &Args<'a>) -> Result<&'a JsonValue, HelperError> {
+    match args.value(0)? {
+        value @ (JsonValue::Object(_) | JsonValue::Array(_)) => Ok(value),
+        value => Err(args.type_error(0, COLLECTION, value)),
+    }
+}
+
+fn found_or_default(args: &Args<'_>, found: Option<&JsonValue>) -> JsonValue {
+    match found {
+        Some(value) if !value.is_null() => value.clone(),
+        _ => args.get(2).cloned().unwrap_or(JsonValue::Null),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render(template: &str) -> String {
+        let context = json!({
+            "payload": { "order": { "id": 7, "items": [{ "name": "Tea", "qty": 2 }, { "name": "Cake" }] } },
+            "msg": { "id": "m-1" },
+            "state": null,
+            "mixed": [1, "two", null, { "a/b": true }],
+            "note": "<b>"
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            render("{{{json payload.order.items.1}}}"),
+            r#"{"name":"Cake"}"#
+        );
+        assert_eq!(render("{{{json mixed}}}"), r#"[1,"two",null,{"a/b":true}]"#);
+        assert_eq!(render("{{json note}}"), "&quot;&lt;b&gt;&quot;");
+    }
+
+    #[test]
+    fn json_pretty() {
+        assert_eq!(
+            render("{{{json_pretty payload.order.items.1}}}"),
+            "{\n  \"name\": \"Cake\"\n}"
+        );
+    }
+
+    #[test]
+    fn get() {
+        assert_eq!(render("{{get payload \"order.items.0.name\"}}"), "Tea");
+        assert_eq!(render("{{get payload \"order.items[1].name\"}}"), "Cake");
+        assert_eq!(render("{{get payload \"order.items.1.qty\" 1}}"), "1");
+        assert_eq!(render("[{{get payload \"order.nope.deeper\"}}]"), "[]");
+        assert_eq!(render("{{get mixed \"1\"}}"), "two");
+    }
+
+    #[test]
+    fn pointer() {
+        assert_eq!(render("{{pointer payload \"/order/items/0/qty\"}}"), "2");
+        assert_eq!(render("{{pointer mixed \"/3/a~1b\"}}"), "true");
+        assert_eq!(render("{{pointer mixed \"/9\" \"none\"}}"), "none");
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(
+            render("{{#each (keys payload.order)}}{{this}};{{/each}}"),
+            "id;items;"
+        );
+        assert_eq!(render("{{{json (keys mixed)}}}"), "[0,1,2,3]");
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(
+            render("{{{json (values payload.order.items.0)}}}"),
+            r#"["Tea",2]"#
+        );
+        assert_eq!(render("{{len (values mixed)}}"), "4");
+    }
+
+    #[test]
+    fn entries() {
+        assert_eq!(
+            render("{{#each (entries payload.order.items.0)}}{{key}}={{value}} {{/each}}"),
+            "name=Tea qty=2 "
+        );
+        assert_eq!(render("{{#each (entries mixed)}}{{key}}{{/each}}"), "0123");
+    }
+
+    #[test]
+    fn has() {
+        assert_eq!(render("{{has payload.order \"items\"}}"), "true");
+        assert_eq!(render("{{has mixed 2}}"), "true");
+        assert_eq!(render("{{has mixed 4}}"), "false");
+        assert_eq!(
+            render("{{#if (has payload \"/order/items/1/qty\")}}qty{{else}}none{{/if}}"),
+            "none"
+        );
+        assert_eq!(render("{{has note \"length\"}}"), "false");
+    }
+
+    #[test]
+    fn roots_render_as_compact_json() {
+        assert_eq!(render("{{{msg}}}"), r#"{"id":"m-1"}"#);
+        assert_eq!(render("{{msg}}"), "{&quot;id&quot;:&quot;m-1&quot;}");
+        assert_eq!(render("[{{state}}]"), "[]");
+        assert_eq!(render("{{payload.order.id}}"), "7");
+    }
+
+    #[test]
+    fn non_collections_are_helper_errors() {
+        assert_eq!(
+            helper_error_detail("{{keys note}}", &json!({ "note": "x" })),
+            "`keys` argument 1 must be an object or array, got a string"
+        );
+    }
+}
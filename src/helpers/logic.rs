@@ -0,0 +1,239 @@
+use std::cmp::Ordering;
+
+use handlebars::JsonTruthy;
+use serde_json::{Value as JsonValue, json};
+
+use super::collection::equal;
+use super::{Args, HelperError, HelperSpec};
+
+// These replace the handlebars built-ins of the same names so every
+// comparison follows one set of rules: numbers compare by value whether
+// they decoded as CBOR integers or floats (`1` equals `1.0`), strings
+// compare by code point, and anything else is only ever equal or not.
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "eq",
+        usage: "{{eq left right}}",
+        call: eq,
+    },
+    HelperSpec {
+        name: "ne",
+        usage: "{{ne left right}}",
+        call: ne,
+    },
+    HelperSpec {
+        name: "lt",
+        usage: "{{lt left right}}",
+        call: lt,
+    },
+    HelperSpec {
+        name: "lte",
+        usage: "{{lte left right}}",
+        call: lte,
+    },
+    HelperSpec {
+        name: "gt",
+        usage: "{{gt left right}}",
+        call: gt,
+    },
+    HelperSpec {
+        name: "gte",
+        usage: "{{gte left right}}",
+        call: gte,
+    },
+    HelperSpec {
+        name: "and",
+        usage: "{{and value value ...}}",
+        call: and,
+    },
+    HelperSpec {
+        name: "or",
+        usage: "{{or value value ...}}",
+        call: or,
+    },
+    HelperSpec {
+        name: "not",
+        usage: "{{not value}}",
+        call: not,
+    },
+    HelperSpec {
+        name: "in",
+        usage: "{{in needle array_object_or_text}}",
+        call: contained,
+    },
+];
+
+// Upper bound for variadic `and` / `or`.
+const MAX_OPERANDS: usize = 16;
+
+fn eq(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    Ok(json!(equal(args.value(0)?, args.value(1)?)))
+}
+
+fn ne(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    Ok(json!(!equal(args.value(0)?, args.value(1)?)))
+}
+
+fn lt(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    ordered(args, Ordering::is_lt)
+}
+
+fn lte(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    ordered(args, Ordering::is_le)
+}
+
+fn gt(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    ordered(args, Ordering::is_gt)
+}
+
+fn gte(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    ordered(args, Ordering::is_ge)
+}
+
+// Only numbers with numbers and strings with strings are ordered; other
+// pairs (including a missing value) are simply not less or greater.
+fn ordered(args: &Args<'_>, accept: fn(Ordering) -> bool) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let order = match (args.value(0)?, args.value(1)?) {
+        (JsonValue::Number(left), JsonValue::Number(right)) => left
+            .as_f64()
+            .zip(right.as_f64())
+            .and_then(|(left, right)| left.partial_cmp(&right)),
+        (JsonValue::String(left), JsonValue::String(right)) => Some(left.cmp(right)),
+        _ => None,
+    };
+    Ok(json!(order.is_some_and(accept)))
+}
+
+// Handlebars truthiness: `false`, `null`, `0`, `""`, `[]` and `{}` are false.
+fn and(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, MAX_OPERANDS)?;
+    Ok(json!(operands(args).all(|value| value.is_truthy(false))))
+}
+
+fn or(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, MAX_OPERANDS)?;
+    Ok(json!(operands(args).any(|value| value.is_truthy(false))))
+}
+
+fn not(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(!args.value(0)?.is_truthy(false)))
+}
+
+// Array membership, object key, or substring; `null` contains nothing.
+fn contained(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let needle = args.value(0)?;
+    Ok(json!(match args.value(1)? {
+        JsonValue::Null => false,
+        JsonValue::Array(items) => items.iter().any(|item| equal(item, needle)),
+        JsonValue::Object(map) => map.contains_key(&args.text(0)?),
+        JsonValue::String(text) => text.contains(&args.text(0)?),
+        value => return Err(args.type_error(1, "an array, object or string", value)),
+    }))
+}
+
+fn operands<'a>(args: &'a Args<'a>) -> impl Iterator<Item = &'a JsonValue> {
+    (0..).map_while(|index| args.get(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render(template: &str) -> String {
+        let context = json!({
+            "count": 3,
+            "ratio": 3.0,
+            "price": 2.5,
+            "name": "Ada",
+            "tags": ["vip", 2],
+            "flags": { "beta": true },
+            "empty": [],
+            "none": null
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    #[test]
+    fn eq() {
+        assert_eq!(render("{{eq count ratio}}"), "true");
+        assert_eq!(render("{{#if (eq name \"Ada\")}}hi{{/if}}"), "hi");
+        assert_eq!(render("{{eq count \"3\"}}"), "false");
+    }
+
+    #[test]
+    fn ne() {
+        assert_eq!(render("{{ne count price}}"), "true");
+        assert_eq!(render("{{ne tags (slice tags 0)}}"), "false");
+    }
+
+    #[test]
+    fn lt() {
+        assert_eq!(render("{{lt price count}}"), "true");
+        assert_eq!(render("{{lt name \"Bob\"}}"), "true");
+        assert_eq!(render("{{lt none 1}}"), "false");
+    }
+
+    #[test]
+    fn lte() {
+        assert_eq!(render("{{lte count ratio}}"), "true");
+    }
+
+    #[test]
+    fn gt() {
+        assert_eq!(render("{{#if (gt count 2)}}many{{else}}few{{/if}}"), "many");
+        assert_eq!(render("{{gt \"10\" 9}}"), "false");
+    }
+
+    #[test]
+    fn gte() {
+        assert_eq!(render("{{gte price 2.5}}"), "true");
+    }
+
+    #[test]
+    fn and() {
+        assert_eq!(render("{{and count name tags}}"), "true");
+        assert_eq!(render("{{and count empty}}"), "false");
+        assert_eq!(
+            render("{{#if (and (gt count 1) (in \"vip\" tags))}}vip{{/if}}"),
+            "vip"
+        );
+    }
+
+    #[test]
+    fn or() {
+        assert_eq!(render("{{or none 0 \"\"}}"), "false");
+        assert_eq!(render("{{or none flags}}"), "true");
+    }
+
+    #[test]
+    fn not() {
+        assert_eq!(render("{{not empty}}"), "true");
+        assert_eq!(render("{{#if (not (eq count 4))}}ok{{/if}}"), "ok");
+    }
+
+    #[test]
+    fn in_() {
+        assert_eq!(render("{{in 2.0 tags}}"), "true");
+        assert_eq!(render("{{in \"beta\" flags}}"), "true");
+        assert_eq!(render("{{in \"d\" name}}"), "true");
+        assert_eq!(render("{{in \"x\" none}}"), "false");
+    }
+
+    #[test]
+    fn invalid_arguments_are_helper_errors() {
+        assert_eq!(
+            helper_error_detail("{{and flag}}", &json!({ "flag": true })),
+            "`and` expects 2 to 16 arguments, got 1"
+        );
+    }
+}
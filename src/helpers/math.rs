@@ -0,0 +1,300 @@
+use serde_json::{Value as JsonValue, json};
+
+use super::{Args, HelperError, HelperSpec};
+
+// Arithmetic keeps the integer/float distinction canonical CBOR makes:
+// integers in, integer out (`{{add 1 2}}` is `3`), any float operand gives a
+// float (`{{add 1 2.0}}` is `3.0`). Integer overflow falls back to float
+// rather than wrapping. Numeric strings are read like numbers.
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "add",
+        usage: "{{add left right}}",
+        call: add,
+    },
+    HelperSpec {
+        name: "sub",
+        usage: "{{sub left right}}",
+        call: sub,
+    },
+    HelperSpec {
+        name: "mul",
+        usage: "{{mul left right}}",
+        call: mul,
+    },
+    HelperSpec {
+        name: "div",
+        usage: "{{div dividend divisor}}",
+        call: div,
+    },
+    HelperSpec {
+        name: "mod",
+        usage: "{{mod dividend divisor}}",
+        call: modulo,
+    },
+    HelperSpec {
+        name: "round",
+        usage: "{{round value [decimals]}}",
+        call: round,
+    },
+    HelperSpec {
+        name: "floor",
+        usage: "{{floor value}}",
+        call: floor,
+    },
+    HelperSpec {
+        name: "ceil",
+        usage: "{{ceil value}}",
+        call: ceil,
+    },
+];
+
+const NUMBER: &str = "a number";
+const DIVISOR: &str = "a non-zero number";
+const DECIMALS: &str = "an integer from 0 to 15";
+const MAX_DECIMALS: usize = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn float(self) -> f64 {
+        match self {
+            Number::Int(value) => value as f64,
+            Number::Float(value) => value,
+        }
+    }
+
+    fn into_json(self) -> JsonValue {
+        match self {
+            Number::Int(value) => json!(value),
+            Number::Float(value) => json!(value),
+        }
+    }
+}
+
+fn add(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    binary(args, i64::checked_add, |left, right| left + right)
+}
+
+fn sub(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    binary(args, i64::checked_sub, |left, right| left - right)
+}
+
+fn mul(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    binary(args, i64::checked_mul, |left, right| left * right)
+}
+
+// Integer division stays an integer only when it is exact: `{{div 6 3}}` is
+// `2`, `{{div 7 2}}` is `3.5`.
+fn div(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let (left, right) = (number(args, 0)?, divisor(args, 1)?);
+    Ok(match (left, right) {
+        (Number::Int(left), Number::Int(right)) if left.checked_rem(right) == Some(0) => {
+            json!(left / right)
+        }
+        _ => json!(left.float() / right.float()),
+    })
+}
+
+// Remainder with the sign of the dividend, like Rust's `%`.
+fn modulo(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let (left, right) = (number(args, 0)?, divisor(args, 1)?);
+    Ok(match (left, right) {
+        (Number::Int(left), Number::Int(right)) => {
+            // `i64::MIN % -1` overflows; its remainder is 0.
+            json!(left.checked_rem(right).unwrap_or(0))
+        }
+        _ => json!(left.float() % right.float()),
+    })
+}
+
+// Half away from zero. Without `decimals` the result is an integer; with
+// them it is a float rounded to that many places.
+fn round(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let value = number(args, 0)?;
+    let Some(decimals) = args.get(1) else {
+        return Ok(whole(value, f64::round));
+    };
+    let decimals = decimals
+        .as_u64()
+        .and_then(|decimals| usize::try_from(decimals).ok())
+        .filter(|decimals| *decimals <= MAX_DECIMALS)
+        .ok_or_else(|| args.type_error(1, DECIMALS, decimals))?;
+    let scale = 10f64.powi(decimals as i32);
+    Ok(json!((value.float() * scale).round() / scale))
+}
+
+fn floor(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(whole(number(args, 0)?, f64::floor))
+}
+
+fn ceil(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(whole(number(args, 0)?, f64::ceil))
+}
+
+fn binary(
+    args: &Args<'_>,
+    int: fn(i64, i64) -> Option<i64>,
+    float: fn(f64, f64) -> f64,
+) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let (left, right) = (number(args, 0)?, number(args, 1)?);
+    let result = match (left, right) {
+        (Number::Int(left), Number::Int(right)) => int(left, right).map(Number::Int),
+        _ => None,
+    };
+    Ok(result
+        .unwrap_or_else(|| Number::Float(float(left.float(), right.float())))
+        .into_json())
+}
+
+// Integers pass through; floats are rounded with `op` and become integers
+// when they fit.
+fn whole(value: Number, op: fn(f64) -> f64) -> JsonValue {
+    match value {
+        Number::Int(value) => json!(value),
+        Number::Float(value) => {
+            let rounded = op(value);
+            if rounded.abs() < 9.0e18 {
+                json!(rounded as i64)
+            } else {
+                json!(rounded)
+            }
+        }
+    }
+}
+
+fn number(args: &Args<'_>, index: usize) -> Result<Number, HelperError> {
+    let value = args.value(index)?;
+    let parsed = match value {
+        JsonValue::Number(number) => number
+            .as_i64()
+            .map(Number::Int)
+            .or_else(|| number.as_f64().map(Number::Float)),
+        JsonValue::String(text) => {
+            let text = text.trim();
+            text.parse::<i64>()
+                .map(Number::Int)
+                .ok()
+                .or_else(|| text.parse::<f64>().ok().map(Number::Float))
+        }
+        _ => None,
+    };
+    parsed
+        .filter(|number| number.float().is_finite())
+        .ok_or_else(|| args.type_error(index, NUMBER, value))
+}
+
+fn divisor(args: &Args<'_>, index: usize) -> Result<Number, HelperError> {
+    let divisor = number(args, index)?;
+    if divisor.float() == 0.0 {
+        return Err(args.type_error(index, DIVISOR, args.value(index)?));
+    }
+    Ok(divisor)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render(template: &str) -> String {
+        let context = json!({ "qty": 3, "price": 2.5, "big": i64::MAX, "text": "4" });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    #[test]
+    fn add() {
+        assert_eq!(render("{{add qty 2}}"), "5");
+        assert_eq!(render("{{add qty 2.0}}"), "5.0");
+        assert_eq!(render("{{add text 1}}"), "5");
+        assert_eq!(render("{{add big 1}}"), "9.223372036854776e+18");
+    }
+
+    #[test]
+    fn sub() {
+        assert_eq!(render("{{sub qty 5}}"), "-2");
+        assert_eq!(render("{{#if (gt (sub qty 1) 1)}}yes{{/if}}"), "yes");
+    }
+
+    #[test]
+    fn mul() {
+        assert_eq!(render("{{mul qty price}}"), "7.5");
+        assert_eq!(render("{{mul qty 4}}"), "12");
+    }
+
+    #[test]
+    fn div() {
+        assert_eq!(render("{{div 6 qty}}"), "2");
+        assert_eq!(render("{{div 7 2}}"), "3.5");
+        assert_eq!(render("{{div price 0.5}}"), "5.0");
+    }
+
+    #[test]
+    fn mod_() {
+        assert_eq!(render("{{mod 7 qty}}"), "1");
+        assert_eq!(render("{{mod -7 qty}}"), "-1");
+        assert_eq!(render("{{mod 7.5 2}}"), "1.5");
+        assert_eq!(render("{{#if (eq (mod qty 2) 1)}}odd{{/if}}"), "odd");
+    }
+
+    #[test]
+    fn round() {
+        assert_eq!(render("{{round price}}"), "3");
+        assert_eq!(render("{{round -2.5}}"), "-3");
+        assert_eq!(render("{{round 3.14159 2}}"), "3.14");
+        assert_eq!(render("{{round qty}}"), "3");
+    }
+
+    #[test]
+    fn floor() {
+        assert_eq!(render("{{floor price}}"), "2");
+        assert_eq!(render("{{floor -2.5}}"), "-3");
+    }
+
+    #[test]
+    fn ceil() {
+        assert_eq!(render("{{ceil price}}"), "3");
+        assert_eq!(render("{{ceil qty}}"), "3");
+    }
+
+    #[test]
+    fn invalid_arguments_are_helper_errors() {
+        for (template, detail) in [
+            (
+                "{{div 1 0}}",
+                "`div` argument 2 must be a non-zero number, got a number",
+            ),
+            (
+                "{{mod 1 0.0}}",
+                "`mod` argument 2 must be a non-zero number, got a number",
+            ),
+            (
+                "{{add 1 list}}",
+                "`add` argument 2 must be a number, got an array",
+            ),
+            (
+                "{{round 1.5 -1}}",
+                "`round` argument 2 must be an integer from 0 to 15, got a number",
+            ),
+        ] {
+            assert_eq!(
+                helper_error_detail(template, &json!({ "list": [] })),
+                detail
+            );
+        }
+    }
+}
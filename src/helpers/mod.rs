@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason,
+    ScopedJson,
+};
+use serde_json::Value as JsonValue;
+
+use crate::context;
+use crate::render;
+
+mod collection;
+pub mod date;
+mod encoding;
+mod json;
+mod logic;
+mod math;
+mod number;
+pub mod random;
+mod regex;
+mod string;
+mod translate;
+
+// Built-in helper: checked arguments in, JSON out. Returning a value (rather
+// than writing output) lets every helper be used as a subexpression, e.g.
+// `{{#if (starts_with payload.sku "A-")}}`.
+pub type HelperFn = fn(&Args<'_>) -> Result<JsonValue, HelperError>;
+
+pub struct HelperSpec {
+    pub name: &'static str,
+    // Shown in diagnostics when the helper is called incorrectly.
+    pub usage: &'static str,
+    pub call: HelperFn,
+}
+
+fn specs() -> impl Iterator<Item = &'static HelperSpec> {
+    string::HELPERS
+        .iter()
+        .chain(date::HELPERS)
+        .chain(number::HELPERS)
+        .chain(translate::HELPERS)
+        .chain(json::HELPERS)
+        .chain(collection::HELPERS)
+        .chain(logic::HELPERS)
+        .chain(math::HELPERS)
+        .chain(encoding::HELPERS)
+        .chain(regex::HELPERS)
+        .chain(random::HELPERS)
+}
+
+pub fn register(registry: &mut Handlebars<'static>) {
+    for spec in specs() {
+        registry.register_helper(spec.name, Box::new(Registered(spec)));
+    }
+}
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    specs().map(|spec| spec.name)
+}
+
+// Helpers that take a `default` as their third argument accept a missing
+// first argument, so optional payload fields never fail a strict render.
+const DEFAULTING: &[&str] = &["get", "pointer"];
+
+struct Registered(&'static HelperSpec);
+
+impl HelperDef for Registered {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        // Handlebars tries helpers before the context, so a bare `{{first}}`
+        // would call the helper even when the payload has a `first` field.
+        // Without arguments the field wins; `{{first items}}` is the helper.
+        // Context roots are the exception: `{{payload}}` and friends are
+        // helpers precisely to render those roots as JSON.
+        if h.params().is_empty() && h.hash().is_empty() && !context::ROOTS.contains(&h.name()) {
+            let field = rc.evaluate(ctx, h.name())?;
+            if !field.is_missing() {
+                return Ok(field);
+            }
+        }
+        // Handlebars resolves missing arguments to `null` even in strict mode,
+        // so they are checked here like plain `{{path}}` output: an error when
+        // strict, a recorded warning when lenient.
+        let defaulted = DEFAULTING.contains(&h.name()) && h.params().len() == 3;
+        let arguments = h.params().iter().skip(usize::from(defaulted));
+        for path in arguments
+            .chain(h.hash().values())
+            .filter(|argument| argument.is_value_missing())
+            .filter_map(|argument| argument.relative_path())
+        {
+            if r.strict_mode() {
+                return Err(RenderError::strict_error(Some(path)));
+            }
+            render::record_missing(path);
+        }
+        let root = ctx.data();
+        let args = Args {
+            spec: self.0,
+            root,
+            locale: root
+                .get("locale")
+                .and_then(JsonValue::as_str)
+                .unwrap_or("en"),
+            params: h.params().iter().map(|param| param.value()).collect(),
+            hash: h
+                .hash()
+                .iter()
+                .map(|(name, value)| (*name, value.value()))
+                .collect(),
+        };
+        let value = (self.0.call)(&args)
+            .map_err(|err| RenderError::from(RenderErrorReason::NestedError(Box::new(err))))?;
+        Ok(ScopedJson::Derived(value))
+    }
+}
+
+// Why a helper call was rejected. Carried through handlebars as a nested
+// error and turned into a `helper_arguments` diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelperError {
+    pub helper: &'static str,
+    pub usage: &'static str,
+    pub problem: Problem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    Arity {
+        min: usize,
+        max: usize,
+        found: usize,
+    },
+    // `argument` is a 1-based position or a hash name.
+    Type {
+        argument: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for HelperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            Problem::Arity { min, max, found } if min == max => write!(
+                f,
+                "`{}` expects {min} argument(s), got {found}",
+                self.helper
+            ),
+            Problem::Arity { min, max, found } => write!(
+                f,
+                "`{}` expects {min} to {max} arguments, got {found}",
+                self.helper
+            ),
+            Problem::Type {
+                argument,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` argument {argument} must be {expected}, got {found}",
+                self.helper
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HelperError {}
+
+// Positional params and hash arguments of one helper call.
+pub struct Args<'a> {
+    spec: &'static HelperSpec,
+    // Render context, for helpers that read node config (e.g. catalogs).
+    root: &'a JsonValue,
+    // Caller locale from the context root, for localized output.
+    locale: &'a str,
+    params: Vec<&'a JsonValue>,
+    hash: BTreeMap<&'a str, &'a JsonValue>,
+}
+
+impl<'a> Args<'a> {
+    pub fn arity(&self, min: usize, max: usize) -> Result<(), HelperError> {
+        let found = self.params.len();
+        if found < min || found > max {
+            return Err(self.error(Problem::Arity { min, max, found }));
+        }
+        Ok(())
+    }
+
+    pub fn locale(&self) -> &'a str {
+        self.locale
+    }
+
+    // Top-level context value such as `config` or `msg`.
+    pub fn root(&self, name: &str) -> Option<&'a JsonValue> {
+        self.root.get(name)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&'a JsonValue> {
+        self.params.get(index).copied()
+    }
+
+    // Scalars are accepted as text; `null` (e.g. a missing value in lenient
+    // mode) reads as the empty string.
+    pub fn text(&self, index: usize) -> Result<String, HelperError> {
+        match self.get(index) {
+            None | Some(JsonValue::Null) => Ok(String::new()),
+            Some(JsonValue::String(text)) => Ok(text.clone()),
+            Some(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => Ok(value.to_string()),
+            Some(value) => Err(self.type_error(index, "a string", value)),
+        }
+    }
+
+    pub fn opt_text(&self, index: usize) -> Result<Option<String>, HelperError> {
+        match self.get(index) {
+            None => Ok(None),
+            Some(_) => self.text(index).map(Some),
+        }
+    }
+
+    pub fn count(&self, index: usize) -> Result<usize, HelperError> {
+        let value = self.value(index)?;
+        value
+            .as_u64()
+            .and_then(|count| usize::try_from(count).ok())
+            .ok_or_else(|| self.type_error(index, "a non-negative integer", value))
+    }
+
+    // Whole numbers (`3`, `3.0`, `"3"`), possibly negative.
+    pub fn integer(&self, index: usize) -> Result<i64, HelperError> {
+        let value = self.value(index)?;
+        let integer = match value {
+            JsonValue::Number(number) => number.as_i64().or_else(|| {
+                number
+                    .as_f64()
+                    .filter(|number| number.fract() == 0.0 && number.abs() < 9.0e15)
+                    .map(|number| number as i64)
+            }),
+            JsonValue::String(text) => text.trim().parse::<i64>().ok(),
+            _ => None,
+        };
+        integer.ok_or_else(|| self.type_error(index, "an integer", value))
+    }
+
+    // Finite numbers, also when sent as numeric strings (form input and
+    // CSV-sourced payloads usually are).
+    pub fn number(&self, index: usize) -> Result<f64, HelperError> {
+        let value = self.value(index)?;
+        let number = match value {
+            JsonValue::Number(number) => number.as_f64(),
+            JsonValue::String(text) => text.trim().parse::<f64>().ok(),
+            _ => None,
+        };
+        number
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| self.type_error(index, "a number", value))
+    }
+
+    // Optional single-character argument such as a pad character.
+    pub fn opt_char(&self, index: usize, default: char) -> Result<char, HelperError> {
+        let Some(text) = self.opt_text(index)? else {
+            return Ok(default);
+        };
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Ok(ch),
+            _ => Err(self.type_error(index, "a single character", self.params[index])),
+        }
+    }
+
+    pub fn hash(&self, name: &str) -> Option<&'a JsonValue> {
+        self.hash.get(name).copied()
+    }
+
+    // Every hash argument as text, with the same coercions as `text`.
+    pub fn hash_texts(&self) -> Result<BTreeMap<String, String>, HelperError> {
+        self.hash
+            .iter()
+            .map(|(name, value)| match value {
+                JsonValue::Null => Ok((name.to_string(), String::new())),
+                JsonValue::String(text) => Ok((name.to_string(), text.clone())),
+                JsonValue::Number(_) | JsonValue::Bool(_) => {
+                    Ok((name.to_string(), value.to_string()))
+                }
+                _ => Err(self.error(Problem::Type {
+                    argument: name.to_string(),
+                    expected: "a string",
+                    found: type_name(value),
+                })),
+            })
+            .collect()
+    }
+
+    pub fn type_error(
+        &self,
+        index: usize,
+        expected: &'static str,
+        found: &JsonValue,
+    ) -> HelperError {
+        self.error(Problem::Type {
+            argument: (index + 1).to_string(),
+            expected,
+            found: type_name(found),
+        })
+    }
+
+    // Any JSON value; only a missing argument is an error.
+    pub fn value(&self, index: usize) -> Result<&'a JsonValue, HelperError> {
+        self.get(index).ok_or_else(|| {
+            self.error(Problem::Arity {
+                min: index + 1,
+                max: index + 1,
+                found: self.params.len(),
+            })
+        })
+    }
+
+    fn error(&self, problem: Problem) -> HelperError {
+        HelperError {
+            helper: self.spec.name,
+            usage: self.spec.usage,
+            problem,
+        }
+    }
+}
+
+pub fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+// The `helper_arguments` detail `template` fails with, for the helper tests.
+#[cfg(test)]
+pub fn helper_error_detail(template: &str, context: &JsonValue) -> String {
+    use crate::diagnostics::CODE_HELPER_ARGUMENTS;
+    use crate::render::{self, RenderOptions};
+
+    let err =
+        render::render_template(template, context, RenderOptions::default()).expect_err(template);
+    let render::RenderError::Render(diagnostic) = err else {
+        panic!("expected render error for {template}, got {err:?}");
+    };
+    assert_eq!(diagnostic.code, CODE_HELPER_ARGUMENTS, "{template}");
+    diagnostic.detail
+}
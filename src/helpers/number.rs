@@ -0,0 +1,274 @@
+use std::sync::OnceLock;
+
+use serde_json::{Value as JsonValue, json};
+
+use super::{Args, HelperError, HelperSpec};
+use crate::i18n;
+use crate::number_bundle::{NumberBundle, NumberFormat, unpack_number_formats_from_cbor};
+
+// Generated by build.rs: static embedded CBOR number format table.
+include!(concat!(env!("OUT_DIR"), "/number_bundle.rs"));
+
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "format_number",
+        usage: "{{format_number value [decimals]}}",
+        call: format_number,
+    },
+    HelperSpec {
+        name: "format_currency",
+        usage: "{{format_currency value currency [decimals]}}",
+        call: format_currency,
+    },
+    HelperSpec {
+        name: "format_percent",
+        usage: "{{format_percent ratio [decimals]}}",
+        call: format_percent,
+    },
+];
+
+const CURRENCY: &str = "an ISO 4217 currency code such as EUR";
+const DECIMALS: &str = "an integer from 0 to 20";
+const MAX_DECIMALS: usize = 20;
+// Fraction digits kept by `format_number` when `decimals` is not given.
+const DEFAULT_MAX_FRACTION: usize = 3;
+
+// Decode once for process lifetime. `en` is always present so every locale
+// chain ends in a format, even if the asset lost its entry.
+static NUMBER_FORMATS: OnceLock<NumberBundle> = OnceLock::new();
+
+fn bundle() -> &'static NumberBundle {
+    NUMBER_FORMATS.get_or_init(|| {
+        let mut bundle = unpack_number_formats_from_cbor(NUMBER_FORMATS_CBOR).unwrap_or_default();
+        bundle
+            .locales
+            .entry("en".to_string())
+            .or_insert_with(|| NumberFormat {
+                decimal: ".".to_string(),
+                group: ",".to_string(),
+                grouping: vec![3],
+                min_grouping: 1,
+                currency: "¤#".to_string(),
+                percent: "#%".to_string(),
+            });
+        bundle
+    })
+}
+
+// Same fallback as translations: exact locale -> base language -> en.
+fn locale_format(locale: &str) -> &'static NumberFormat {
+    let locales = &bundle().locales;
+    i18n::locale_chain(locale)
+        .iter()
+        .find_map(|candidate| locales.get(candidate))
+        .unwrap_or(&locales["en"])
+}
+
+// Grouped with the caller's separators; up to three fraction digits unless
+// `decimals` fixes the count.
+fn format_number(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let value = args.number(0)?;
+    let decimals = decimals(args, 1)?;
+    let format = locale_format(args.locale());
+    let (negative, digits) = localize(format, value, decimals);
+    Ok(json!(signed(negative, digits)))
+}
+
+// Places the currency symbol per locale; `decimals` defaults to the
+// currency's minor unit digits (0 for JPY, 2 for EUR).
+fn format_currency(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let value = args.number(0)?;
+    let code = args.text(1)?.trim().to_ascii_uppercase();
+    if code.len() != 3 || !code.bytes().all(|byte| byte.is_ascii_uppercase()) {
+        return Err(args.type_error(1, CURRENCY, args.get(1).unwrap_or(&JsonValue::Null)));
+    }
+    let currency = bundle().currencies.get(&code);
+    let symbol = currency.map_or(code.as_str(), |currency| currency.symbol.as_str());
+    let digits = currency.map_or(2, |currency| usize::from(currency.digits));
+    let decimals = decimals(args, 2)?.unwrap_or(digits);
+    let format = locale_format(args.locale());
+    let (negative, digits) = localize(format, value, Some(decimals));
+    Ok(json!(signed(
+        negative,
+        place_symbol(&format.currency, &digits, symbol)
+    )))
+}
+
+// `ratio` is a fraction: 0.25 renders as 25%.
+fn format_percent(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let value = args.number(0)? * 100.0;
+    let decimals = decimals(args, 1)?.unwrap_or(0);
+    let format = locale_format(args.locale());
+    let (negative, digits) = localize(format, value, Some(decimals));
+    Ok(json!(signed(
+        negative,
+        format.percent.replace('#', &digits)
+    )))
+}
+
+fn decimals(args: &Args<'_>, index: usize) -> Result<Option<usize>, HelperError> {
+    let Some(value) = args.get(index) else {
+        return Ok(None);
+    };
+    match value
+        .as_u64()
+        .and_then(|decimals| usize::try_from(decimals).ok())
+    {
+        Some(decimals) if decimals <= MAX_DECIMALS => Ok(Some(decimals)),
+        _ => Err(args.type_error(index, DECIMALS, value)),
+    }
+}
+
+// Rounds `value` and renders its magnitude with the locale separators. The
+// sign is returned separately so patterns can wrap the digits; values that
+// round to zero are never negative.
+fn localize(format: &NumberFormat, value: f64, decimals: Option<usize>) -> (bool, String) {
+    let mut fixed = format!(
+        "{:.*}",
+        decimals.unwrap_or(DEFAULT_MAX_FRACTION),
+        value.abs()
+    );
+    if decimals.is_none() && fixed.contains('.') {
+        fixed.truncate(fixed.trim_end_matches('0').trim_end_matches('.').len());
+    }
+    let negative = value < 0.0 && fixed.bytes().any(|byte| matches!(byte, b'1'..=b'9'));
+    let (integer, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+    let mut out = group(format, integer);
+    if !fraction.is_empty() {
+        out.push_str(&format.decimal);
+        out.push_str(fraction);
+    }
+    (negative, out)
+}
+
+fn group(format: &NumberFormat, integer: &str) -> String {
+    let primary = format.grouping.first().map_or(3, |size| usize::from(*size));
+    let secondary = format
+        .grouping
+        .get(1)
+        .map_or(primary, |size| usize::from(*size));
+    let min_grouping = usize::from(format.min_grouping.max(1));
+    if primary == 0 || secondary == 0 || integer.len() < primary + min_grouping {
+        return integer.to_string();
+    }
+    let (mut rest, last) = integer.split_at(integer.len() - primary);
+    let mut groups = vec![last];
+    while rest.len() > secondary {
+        let (head, tail) = rest.split_at(rest.len() - secondary);
+        groups.push(tail);
+        rest = head;
+    }
+    groups.push(rest);
+    groups.reverse();
+    groups.join(&format.group)
+}
+
+// Alphabetic symbols (`CHF`, `kr`) get a no-break space where the pattern
+// would glue them to the digits, as CLDR currency spacing does.
+fn place_symbol(pattern: &str, digits: &str, symbol: &str) -> String {
+    let pattern = if symbol.chars().last().is_some_and(char::is_alphabetic) {
+        pattern.replace("¤#", "¤\u{a0}#")
+    } else {
+        pattern.to_string()
+    };
+    let pattern = if symbol.chars().next().is_some_and(char::is_alphabetic) {
+        pattern.replace("#¤", "#\u{a0}¤")
+    } else {
+        pattern
+    };
+    pattern.replace('#', digits).replace('¤', symbol)
+}
+
+fn signed(negative: bool, text: String) -> String {
+    if negative { format!("-{text}") } else { text }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render_in(locale: &str, template: &str) -> String {
+        let context = json!({
+            "locale": locale,
+            "big": 1_234_567.891,
+            "price": "1234.5",
+            "refund": -1234.5,
+            "ratio": 0.256
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    fn render(template: &str) -> String {
+        render_in("en", template)
+    }
+
+    #[test]
+    fn format_number() {
+        assert_eq!(render("{{format_number big}}"), "1,234,567.891");
+        assert_eq!(render("{{format_number big 1}}"), "1,234,567.9");
+        assert_eq!(render("{{format_number price}}"), "1,234.5");
+        assert_eq!(render("{{format_number -0.001 2}}"), "0.00");
+        assert_eq!(render_in("de", "{{format_number big 2}}"), "1.234.567,89");
+        assert_eq!(render_in("de-AT", "{{format_number refund}}"), "-1.234,5");
+        assert_eq!(
+            render_in("fr-FR", "{{format_number big 0}}"),
+            "1\u{202f}234\u{202f}568"
+        );
+        assert_eq!(render_in("hi", "{{format_number 1234567}}"), "12,34,567");
+        assert_eq!(render_in("es", "{{format_number 1234}}"), "1234");
+        assert_eq!(render_in("es", "{{format_number 12345}}"), "12.345");
+        assert_eq!(render_in("xx", "{{format_number 1234}}"), "1,234");
+    }
+
+    #[test]
+    fn format_currency() {
+        assert_eq!(render("{{format_currency refund \"usd\"}}"), "-$1,234.50");
+        assert_eq!(
+            render_in("de", "{{format_currency price \"EUR\"}}"),
+            "1.234,50\u{a0}€"
+        );
+        assert_eq!(
+            render_in("ja", "{{format_currency 1234.6 \"JPY\"}}"),
+            "¥1,235"
+        );
+        assert_eq!(render("{{format_currency 5 \"CHF\" 0}}"), "CHF\u{a0}5");
+        assert_eq!(render("{{format_currency 5 \"XTS\"}}"), "XTS\u{a0}5.00");
+    }
+
+    #[test]
+    fn format_percent() {
+        assert_eq!(render("{{format_percent ratio}}"), "26%");
+        assert_eq!(render("{{format_percent ratio 1}}"), "25.6%");
+        assert_eq!(render_in("de", "{{format_percent 0.5}}"), "50\u{a0}%");
+        assert_eq!(render_in("tr", "{{format_percent 0.5}}"), "%50");
+    }
+
+    #[test]
+    fn invalid_arguments_are_helper_errors() {
+        for (template, detail) in [
+            (
+                "{{format_number \"abc\"}}",
+                "`format_number` argument 1 must be a number, got a string".to_string(),
+            ),
+            (
+                "{{format_number 1 21}}",
+                format!("`format_number` argument 2 must be {DECIMALS}, got a number"),
+            ),
+            (
+                "{{format_currency 1 \"euro\"}}",
+                format!("`format_currency` argument 2 must be {CURRENCY}, got a string"),
+            ),
+        ] {
+            assert_eq!(helper_error_detail(template, &json!({})), detail);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use serde_json::{Value as JsonValue, json};
+
+use super::{Args, HelperError, HelperSpec};
+
+// Every render draws from one SplitMix64 stream, started on the first
+// random helper call. By default the stream is seeded from `wasi:random`
+// (through std's `RandomState`), so greetings vary. With
+// `templates.deterministic` (or an explicit `templates.seed`) the seed is
+// `templates.seed`, else `msg.id`, so replays and golden tests render the
+// same text. A context field named `uuid` is rendered instead of a new id
+// (see `helpers::Registered`).
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "uuid",
+        usage: "{{uuid}}",
+        call: uuid,
+    },
+    HelperSpec {
+        name: "random_int",
+        usage: "{{random_int min max}}",
+        call: random_int,
+    },
+    HelperSpec {
+        name: "pick",
+        usage: "{{pick array}} or {{pick choice choice ...}}",
+        call: pick,
+    },
+    HelperSpec {
+        name: "shuffle",
+        usage: "{{shuffle array}}",
+        call: shuffle,
+    },
+];
+
+// Upper bound for `pick` with inline choices.
+const MAX_CHOICES: usize = 64;
+
+thread_local! {
+    static STREAM: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+// Starts a fresh stream; the renderer calls this before every render so
+// seeded output depends only on the seed and the template.
+pub fn reset() {
+    STREAM.with(|stream| stream.set(None));
+}
+
+// Version 4 UUID, lowercase hyphenated.
+fn uuid(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(0, 0)?;
+    let mut bytes = [0u8; 16];
+    bytes[..8].copy_from_slice(&next(args).to_be_bytes());
+    bytes[8..].copy_from_slice(&next(args).to_be_bytes());
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    Ok(json!(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )))
+}
+
+// Inclusive on both ends.
+fn random_int(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let (min, max) = (args.integer(0)?, args.integer(1)?);
+    if max < min {
+        return Err(args.type_error(1, "an integer not below `min`", args.value(1)?));
+    }
+    let span = (i128::from(max) - i128::from(min) + 1) as u64;
+    Ok(json!(min.wrapping_add(below(args, span) as i64)))
+}
+
+// One element of an array (`null` when empty), or one of several inline
+// choices: `{{pick "Hi" "Hello" "Hey"}}`.
+fn pick(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, MAX_CHOICES)?;
+    let choices: Vec<&JsonValue> = match (args.value(0)?, args.get(1)) {
+        (JsonValue::Array(items), None) => items.iter().collect(),
+        _ => (0..).map_while(|index| args.get(index)).collect(),
+    };
+    if choices.is_empty() {
+        return Ok(JsonValue::Null);
+    }
+    let index = below(args, choices.len() as u64) as usize;
+    Ok(choices[index].clone())
+}
+
+// A shuffled copy (Fisher-Yates).
+fn shuffle(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let mut items = match args.value(0)? {
+        JsonValue::Array(items) => items.clone(),
+        value => return Err(args.type_error(0, "an array", value)),
+    };
+    for last in (1..items.len()).rev() {
+        let other = below(args, last as u64 + 1) as usize;
+        items.swap(last, other);
+    }
+    Ok(JsonValue::Array(items))
+}
+
+// Uniform in `0..bound` (multiply-shift; the bias is below 2^-32 for any
+// bound a template uses). A zero bound means the full `u64` range.
+fn below(args: &Args<'_>, bound: u64) -> u64 {
+    if bound == 0 {
+        return next(args);
+    }
+    ((u128::from(next(args)) * u128::from(bound)) >> 64) as u64
+}
+
+fn next(args: &Args<'_>) -> u64 {
+    STREAM.with(|stream| {
+        let state = stream
+            .get()
+            .unwrap_or_else(|| seed(args))
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        stream.set(Some(state));
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
+}
+
+fn seed(args: &Args<'_>) -> u64 {
+    let templates = args
+        .root("config")
+        .and_then(|config| config.get("templates"));
+    let setting = |name: &str| templates.and_then(|templates| templates.get(name));
+    let seed = setting("seed").filter(|seed| !seed.is_null());
+    let deterministic = seed.is_some()
+        || setting("deterministic")
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(false);
+    if !deterministic {
+        return RandomState::new().build_hasher().finish();
+    }
+    let material = match seed.or_else(|| args.root("msg").and_then(|msg| msg.get("id"))) {
+        Some(JsonValue::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+        None => String::new(),
+    };
+    let digest = blake3::hash(material.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest.as_bytes()[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value as JsonValue, json};
+
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render_with(template: &str, templates: JsonValue, msg_id: &str) -> String {
+        let context = json!({
+            "config": { "templates": templates },
+            "msg": { "id": msg_id },
+            "greetings": ["Hi", "Hello", "Hey"],
+            "empty": []
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    fn seeded(template: &str) -> String {
+        render_with(template, json!({ "seed": "golden" }), "m-1")
+    }
+
+    #[test]
+    fn uuid() {
+        let id = seeded("{{uuid}}");
+        assert_eq!(id, seeded("{{uuid}}"));
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        let pair = seeded("{{uuid}} {{uuid}}");
+        assert_eq!(&pair[..36], id);
+        assert_ne!(&pair[37..], id);
+    }
+
+    #[test]
+    fn payload_fields_shadow_generators() {
+        let context = json!({ "uuid": "abc", "greetings": ["Hi"] });
+        let rendered = render_template(
+            "id={{uuid}} {{pick greetings}}",
+            &context,
+            RenderOptions::default(),
+        )
+        .expect("render")
+        .text;
+        assert_eq!(rendered, "id=abc Hi");
+    }
+
+    #[test]
+    fn random_int() {
+        let roll = "{{#each greetings}}{{random_int 1 6}}{{/each}}";
+        assert_eq!(seeded(roll), seeded(roll));
+        assert!(
+            seeded(roll)
+                .chars()
+                .all(|digit| ('1'..='6').contains(&digit))
+        );
+        assert_eq!(seeded("{{random_int 7 7}}"), "7");
+        let unseeded = render_with("{{random_int -3 3}}", json!({}), "m-1");
+        assert!((-3..=3).contains(&unseeded.parse::<i64>().expect("integer")));
+    }
+
+    #[test]
+    fn pick() {
+        let replay = |msg_id| {
+            render_with(
+                "{{pick greetings}}",
+                json!({ "deterministic": true }),
+                msg_id,
+            )
+        };
+        assert_eq!(replay("m-1"), replay("m-1"));
+        assert!(["Hi", "Hello", "Hey"].contains(&replay("m-2").as_str()));
+        assert!(["a", "b"].contains(&seeded("{{pick \"a\" \"b\"}}").as_str()));
+        assert_eq!(seeded("[{{pick empty}}]"), "[]");
+    }
+
+    #[test]
+    fn shuffle() {
+        let shuffled = seeded("{{#each (shuffle greetings)}}{{this}} {{/each}}");
+        assert_eq!(
+            shuffled,
+            seeded("{{#each (shuffle greetings)}}{{this}} {{/each}}")
+        );
+        let mut words: Vec<&str> = shuffled.split_whitespace().collect();
+        words.sort_unstable();
+        assert_eq!(words, ["Hello", "Hey", "Hi"]);
+    }
+
+    #[test]
+    fn invalid_arguments_are_helper_errors() {
+        for (template, detail) in [
+            (
+                "{{random_int 5 1}}",
+                "`random_int` argument 2 must be an integer not below `min`, got a number",
+            ),
+            (
+                "{{shuffle \"abc\"}}",
+                "`shuffle` argument 1 must be an array, got a string",
+            ),
+        ] {
+            assert_eq!(helper_error_detail(template, &json!({})), detail);
+        }
+    }
+}
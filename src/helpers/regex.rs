@@ -0,0 +1,231 @@
+use std::sync::Arc;
+
+use regex::{Regex, RegexBuilder};
+use serde_json::{Map, Value as JsonValue, json};
+
+use super::{Args, HelperError, HelperSpec};
+use crate::cache;
+
+// Rust `regex` syntax (no backreferences or lookaround), so matching is
+// linear in the input. With the pattern, compiled-size and input caps below
+// a single call stays in the low milliseconds, far inside the manifest's
+// wall-time budget. Compiled patterns live in `cache`.
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "regex_match",
+        usage: "{{regex_match text pattern}}",
+        call: regex_match,
+    },
+    HelperSpec {
+        name: "regex_capture",
+        usage: "{{regex_capture text pattern}}",
+        call: regex_capture,
+    },
+    HelperSpec {
+        name: "regex_replace",
+        usage: "{{regex_replace text pattern replacement}}",
+        call: regex_replace,
+    },
+    HelperSpec {
+        name: "regex_split",
+        usage: "{{regex_split text pattern}}",
+        call: regex_split,
+    },
+];
+
+pub const MAX_PATTERN_LEN: usize = 1024;
+pub const MAX_INPUT_LEN: usize = 64 * 1024;
+pub const MAX_OUTPUT_LEN: usize = 1024 * 1024;
+const COMPILED_SIZE_LIMIT: usize = 256 * 1024;
+const NEST_LIMIT: u32 = 32;
+
+const PATTERN: &str = "a valid regular expression of at most 1024 bytes";
+const INPUT: &str = "text of at most 65536 bytes";
+const REPLACEMENT: &str = "a replacement keeping the result within 1048576 bytes";
+
+fn regex_match(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let (text, regex) = (input(args)?, pattern(args)?);
+    Ok(json!(regex.is_match(&text)))
+}
+
+// First match as `{ "0": whole, "1": group, name: group, ... }`: every group
+// by index and named groups also by name, `null` when a group did not
+// take part. No match gives `null`, so `{{#with (regex_capture ...)}}`
+// doubles as the test.
+fn regex_capture(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let (text, regex) = (input(args)?, pattern(args)?);
+    let Some(captures) = regex.captures(&text) else {
+        return Ok(JsonValue::Null);
+    };
+    let mut groups = Map::new();
+    for (index, name) in regex.capture_names().enumerate() {
+        let group = json!(captures.get(index).map(|group| group.as_str()));
+        if let Some(name) = name {
+            groups.insert(name.to_string(), group.clone());
+        }
+        groups.insert(index.to_string(), group);
+    }
+    Ok(JsonValue::Object(groups))
+}
+
+// Replaces every match; `$1`, `${name}` and `$$` work in the replacement.
+// An empty pattern matches between every character, so the result is built
+// match by match and stops at `MAX_OUTPUT_LEN` instead of growing to
+// input length times replacement length.
+fn regex_replace(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(3, 3)?;
+    let (text, regex) = (input(args)?, pattern(args)?);
+    let replacement = args.text(2)?;
+    let too_long = || args.type_error(2, REPLACEMENT, args.get(2).unwrap_or(&JsonValue::Null));
+    let mut out = String::new();
+    let mut last = 0;
+    for captures in regex.captures_iter(&text) {
+        let Some(found) = captures.get(0) else {
+            continue;
+        };
+        out.push_str(&text[last..found.start()]);
+        captures.expand(&replacement, &mut out);
+        last = found.end();
+        if out.len() > MAX_OUTPUT_LEN {
+            return Err(too_long());
+        }
+    }
+    out.push_str(&text[last..]);
+    if out.len() > MAX_OUTPUT_LEN {
+        return Err(too_long());
+    }
+    Ok(json!(out))
+}
+
+fn regex_split(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let (text, regex) = (input(args)?, pattern(args)?);
+    Ok(json!(regex.split(&text).collect::<Vec<_>>()))
+}
+
+fn input(args: &Args<'_>) -> Result<String, HelperError> {
+    let text = args.text(0)?;
+    if text.len() > MAX_INPUT_LEN {
+        return Err(args.type_error(0, INPUT, args.value(0)?));
+    }
+    Ok(text)
+}
+
+fn pattern(args: &Args<'_>) -> Result<Arc<Regex>, HelperError> {
+    let pattern = args.text(1)?;
+    let invalid = || args.type_error(1, PATTERN, args.get(1).unwrap_or(&JsonValue::Null));
+    if pattern.len() > MAX_PATTERN_LEN {
+        return Err(invalid());
+    }
+    cache::compiled_regex(&pattern, |pattern| {
+        RegexBuilder::new(pattern)
+            .size_limit(COMPILED_SIZE_LIMIT)
+            .dfa_size_limit(COMPILED_SIZE_LIMIT)
+            .nest_limit(NEST_LIMIT)
+            .build()
+    })
+    .map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render(template: &str) -> String {
+        let context = json!({
+            "text": "Where is order AB-1234? Also ab-77.",
+            "csv": "tea, cake ,scones"
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    #[test]
+    fn regex_match() {
+        assert_eq!(
+            render(r#"{{regex_match text "\\b[A-Z]{2}-\\d+\\b"}}"#),
+            "true"
+        );
+        assert_eq!(
+            render(r#"{{#if (regex_match text "^\\d+$")}}id{{else}}text{{/if}}"#),
+            "text"
+        );
+    }
+
+    #[test]
+    fn regex_capture() {
+        assert_eq!(
+            render(
+                r#"{{#with (regex_capture text "(?<prefix>[A-Z]{2})-(?<number>\\d+)")}}{{prefix}}/{{number}}{{/with}}"#
+            ),
+            "AB/1234"
+        );
+        assert_eq!(
+            render(r#"{{{json (regex_capture text "(?i)(ab)-(\\d+)(x)?")}}}"#),
+            r#"{"0":"AB-1234","1":"AB","2":"1234","3":null}"#
+        );
+        assert_eq!(render(r#"[{{regex_capture text "zz"}}]"#), "[]");
+    }
+
+    #[test]
+    fn regex_replace() {
+        assert_eq!(
+            render(r##"{{regex_replace text "(?i)([a-z]{2})-(\\d+)" "#$2"}}"##),
+            "Where is order #1234? Also #77."
+        );
+        assert_eq!(
+            render(r#"{{regex_replace "a.b" "(?<x>\\.)" "[${x}]"}}"#),
+            "a[.]b"
+        );
+    }
+
+    #[test]
+    fn regex_split() {
+        assert_eq!(
+            render(r#"{{#each (regex_split csv "\\s*,\\s*")}}<{{this}}>{{/each}}"#),
+            "<tea><cake><scones>"
+        );
+    }
+
+    #[test]
+    fn invalid_and_oversized_arguments_are_helper_errors() {
+        let long_pattern = "a".repeat(super::MAX_PATTERN_LEN + 1);
+        let long_text = "a".repeat(super::MAX_INPUT_LEN + 1);
+        let max_text = "a".repeat(super::MAX_INPUT_LEN);
+        for (template, context, detail) in [
+            (
+                r#"{{regex_match "x" "("}}"#,
+                json!({}),
+                "`regex_match` argument 2 must be a valid regular expression of at most 1024 bytes, got a string",
+            ),
+            (
+                "{{regex_match \"x\" pattern}}",
+                json!({ "pattern": long_pattern }),
+                "`regex_match` argument 2 must be a valid regular expression of at most 1024 bytes, got a string",
+            ),
+            (
+                r#"{{regex_match "x" "\\w{1000}{1000}"}}"#,
+                json!({}),
+                "`regex_match` argument 2 must be a valid regular expression of at most 1024 bytes, got a string",
+            ),
+            (
+                "{{regex_split text \",\"}}",
+                json!({ "text": long_text }),
+                "`regex_split` argument 1 must be text of at most 65536 bytes, got a string",
+            ),
+            (
+                "{{regex_replace text \"\" text}}",
+                json!({ "text": max_text }),
+                "`regex_replace` argument 3 must be a replacement keeping the result within 1048576 bytes, got a string",
+            ),
+        ] {
+            assert_eq!(helper_error_detail(template, &context), detail);
+        }
+    }
+}
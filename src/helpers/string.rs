@@ -0,0 +1,346 @@
+use serde_json::{Value as JsonValue, json};
+
+use super::{Args, HelperError, HelperSpec};
+
+pub const HELPERS: &[HelperSpec] = &[
+    HelperSpec {
+        name: "upper",
+        usage: "{{upper text}}",
+        call: upper,
+    },
+    HelperSpec {
+        name: "lower",
+        usage: "{{lower text}}",
+        call: lower,
+    },
+    HelperSpec {
+        name: "capitalize",
+        usage: "{{capitalize text}}",
+        call: capitalize,
+    },
+    HelperSpec {
+        name: "title_case",
+        usage: "{{title_case text}}",
+        call: title_case,
+    },
+    HelperSpec {
+        name: "trim",
+        usage: "{{trim text [chars]}}",
+        call: trim,
+    },
+    HelperSpec {
+        name: "truncate",
+        usage: "{{truncate text length [ellipsis]}}",
+        call: truncate,
+    },
+    HelperSpec {
+        name: "pad_left",
+        usage: "{{pad_left text width [char]}}",
+        call: pad_left,
+    },
+    HelperSpec {
+        name: "pad_right",
+        usage: "{{pad_right text width [char]}}",
+        call: pad_right,
+    },
+    HelperSpec {
+        name: "replace",
+        usage: "{{replace text from to}}",
+        call: replace,
+    },
+    HelperSpec {
+        name: "split",
+        usage: "{{split text separator}}",
+        call: split,
+    },
+    HelperSpec {
+        name: "starts_with",
+        usage: "{{starts_with text prefix}}",
+        call: starts_with,
+    },
+    HelperSpec {
+        name: "contains",
+        usage: "{{contains text_or_array needle}}",
+        call: contains,
+    },
+];
+
+const DEFAULT_ELLIPSIS: &str = "…";
+// Padding is built eagerly, so the width is capped like regex inputs.
+pub const MAX_PAD_WIDTH: usize = 64 * 1024;
+const PAD_WIDTH: &str = "a non-negative integer of at most 65536";
+
+fn upper(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(args.text(0)?.to_uppercase()))
+}
+
+fn lower(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(args.text(0)?.to_lowercase()))
+}
+
+fn capitalize(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    Ok(json!(capitalize_first(&args.text(0)?)))
+}
+
+// Upper-cases the first letter of every word and lower-cases the rest;
+// whitespace is preserved as written.
+fn title_case(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let text = args.text(0)?;
+    let mut out = String::with_capacity(text.len());
+    let mut word_start = true;
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            out.push(ch);
+            word_start = true;
+        } else if word_start {
+            out.extend(ch.to_uppercase());
+            word_start = false;
+        } else {
+            out.extend(ch.to_lowercase());
+        }
+    }
+    Ok(json!(out))
+}
+
+// Trims whitespace, or any of the characters in `chars` when given.
+fn trim(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 2)?;
+    let text = args.text(0)?;
+    let trimmed = match args.opt_text(1)? {
+        Some(chars) => text.trim_matches(|ch| chars.contains(ch)),
+        None => text.trim(),
+    };
+    Ok(json!(trimmed))
+}
+
+// Shortens `text` to at most `length` characters, ellipsis included.
+fn truncate(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let text = args.text(0)?;
+    let length = args.count(1)?;
+    let ellipsis = args
+        .opt_text(2)?
+        .unwrap_or_else(|| DEFAULT_ELLIPSIS.to_string());
+    if text.chars().count() <= length {
+        return Ok(json!(text));
+    }
+    let ellipsis_len = ellipsis.chars().count();
+    if ellipsis_len >= length {
+        return Ok(json!(text.chars().take(length).collect::<String>()));
+    }
+    let mut out: String = text.chars().take(length - ellipsis_len).collect();
+    out.push_str(&ellipsis);
+    Ok(json!(out))
+}
+
+fn pad_left(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    pad(args, true)
+}
+
+fn pad_right(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    pad(args, false)
+}
+
+fn pad(args: &Args<'_>, left: bool) -> Result<JsonValue, HelperError> {
+    args.arity(2, 3)?;
+    let text = args.text(0)?;
+    let width = args.count(1)?;
+    if width > MAX_PAD_WIDTH {
+        return Err(args.type_error(1, PAD_WIDTH, args.value(1)?));
+    }
+    let fill = args.opt_char(2, ' ')?;
+    let padding: String =
+        std::iter::repeat_n(fill, width.saturating_sub(text.chars().count())).collect();
+    Ok(json!(if left {
+        padding + &text
+    } else {
+        text + &padding
+    }))
+}
+
+fn replace(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(3, 3)?;
+    let text = args.text(0)?;
+    let from = args.text(1)?;
+    if from.is_empty() {
+        return Ok(json!(text));
+    }
+    Ok(json!(text.replace(&from, &args.text(2)?)))
+}
+
+// Splits into an array for `#each`; an empty separator splits characters.
+fn split(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    let text = args.text(0)?;
+    let separator = args.text(1)?;
+    let parts: Vec<String> = if separator.is_empty() {
+        text.chars().map(String::from).collect()
+    } else {
+        text.split(separator.as_str()).map(String::from).collect()
+    };
+    Ok(json!(parts))
+}
+
+fn starts_with(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    Ok(json!(args.text(0)?.starts_with(&args.text(1)?)))
+}
+
+// Substring test for strings, membership test for arrays.
+fn contains(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(2, 2)?;
+    if let Some(JsonValue::Array(items)) = args.get(0) {
+        let needle = args.get(1).unwrap_or(&JsonValue::Null);
+        return Ok(json!(items.contains(needle)));
+    }
+    Ok(json!(args.text(0)?.contains(&args.text(1)?)))
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::helpers::{HelperError, Problem, helper_error_detail};
+    use crate::render::{RenderOptions, render_template};
+
+    fn render(template: &str) -> String {
+        let context = json!({
+            "name": "ada lovelace",
+            "padded": "  hi  ",
+            "sku": "A-100",
+            "tags": ["vip", "beta"],
+            "n": 42
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    fn helper_error(template: &str) -> String {
+        helper_error_detail(template, &json!({ "list": [1] }))
+    }
+
+    #[test]
+    fn upper() {
+        assert_eq!(render("{{upper name}}"), "ADA LOVELACE");
+        assert_eq!(render("{{upper n}}"), "42");
+    }
+
+    #[test]
+    fn lower() {
+        assert_eq!(render("{{lower \"ÉCOLE\"}}"), "école");
+    }
+
+    #[test]
+    fn capitalize() {
+        assert_eq!(render("{{capitalize name}}"), "Ada lovelace");
+        assert_eq!(render("{{capitalize \"\"}}"), "");
+    }
+
+    #[test]
+    fn title_case() {
+        assert_eq!(render("{{title_case \"hello  wORLD\"}}"), "Hello  World");
+    }
+
+    #[test]
+    fn trim() {
+        assert_eq!(render("[{{trim padded}}]"), "[hi]");
+        assert_eq!(render("{{trim \"--x--\" \"-\"}}"), "x");
+    }
+
+    #[test]
+    fn truncate() {
+        assert_eq!(render("{{truncate name 8}}"), "ada lov…");
+        assert_eq!(render("{{truncate name 8 \"...\"}}"), "ada l...");
+        assert_eq!(render("{{truncate name 50}}"), "ada lovelace");
+        assert_eq!(render("{{truncate name 2 \"...\"}}"), "ad");
+    }
+
+    #[test]
+    fn pad_left() {
+        assert_eq!(render("{{pad_left n 5 \"0\"}}"), "00042");
+        assert_eq!(render("[{{pad_left \"abc\" 2}}]"), "[abc]");
+    }
+
+    #[test]
+    fn pad_right() {
+        assert_eq!(render("[{{pad_right \"ab\" 4}}]"), "[ab  ]");
+    }
+
+    #[test]
+    fn replace() {
+        assert_eq!(render("{{replace sku \"-\" \"/\"}}"), "A/100");
+        assert_eq!(render("{{replace sku \"\" \"x\"}}"), "A-100");
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(
+            render("{{#each (split \"a,b,c\" \",\")}}<{{this}}>{{/each}}"),
+            "<a><b><c>"
+        );
+        assert_eq!(
+            render("{{#each (split \"ab\" \"\")}}{{this}}.{{/each}}"),
+            "a.b."
+        );
+    }
+
+    #[test]
+    fn starts_with() {
+        assert_eq!(render("{{#if (starts_with sku \"A-\")}}yes{{/if}}"), "yes");
+        assert_eq!(render("{{starts_with sku \"B\"}}"), "false");
+    }
+
+    #[test]
+    fn contains() {
+        assert_eq!(render("{{contains name \"love\"}}"), "true");
+        assert_eq!(render("{{#if (contains tags \"vip\")}}vip{{/if}}"), "vip");
+        assert_eq!(render("{{contains tags \"gold\"}}"), "false");
+    }
+
+    #[test]
+    fn arity_and_type_errors_are_structured() {
+        assert_eq!(
+            helper_error("{{truncate \"abc\"}}"),
+            "`truncate` expects 2 to 3 arguments, got 1"
+        );
+        assert_eq!(
+            helper_error("{{upper list}}"),
+            "`upper` argument 1 must be a string, got an array"
+        );
+        assert_eq!(
+            helper_error("{{pad_left \"a\" -1}}"),
+            "`pad_left` argument 2 must be a non-negative integer, got a number"
+        );
+        assert_eq!(
+            helper_error("{{pad_right \"a\" 65537}}"),
+            "`pad_right` argument 2 must be a non-negative integer of at most 65536, got a number"
+        );
+        assert_eq!(
+            helper_error("{{pad_left \"a\" 3 \"ab\"}}"),
+            "`pad_left` argument 3 must be a single character, got a string"
+        );
+        let err = HelperError {
+            helper: "upper",
+            usage: "{{upper text}}",
+            problem: Problem::Arity {
+                min: 1,
+                max: 1,
+                found: 2,
+            },
+        };
+        assert_eq!(err.to_string(), "`upper` expects 1 argument(s), got 2");
+    }
+}
@@ -0,0 +1,115 @@
+use serde_json::{Value as JsonValue, json};
+
+use super::{Args, HelperError, HelperSpec};
+use crate::i18n;
+
+pub const HELPERS: &[HelperSpec] = &[HelperSpec {
+    name: "t",
+    usage: "{{t \"key\" name=value ...}}",
+    call: t,
+}];
+
+// Translates `key` for the caller locale, formatting it as ICU MessageFormat
+// with the hash arguments (`{{t "inbox" count=n}}` can pick a plural form).
+// Each locale in the fallback chain is tried against the tenant catalog
+// (`templates.messages`) first and the embedded bundle second, so a tenant
+// `en` entry never hides a bundled `fr` translation. Unknown keys render as
+// the key itself, like `i18n::t`.
+fn t(args: &Args<'_>) -> Result<JsonValue, HelperError> {
+    args.arity(1, 1)?;
+    let key = args.text(0)?;
+    if key.trim().is_empty() {
+        return Err(args.type_error(
+            0,
+            "a translation key",
+            args.get(0).unwrap_or(&JsonValue::Null),
+        ));
+    }
+    let named = args.hash_texts()?;
+    let catalogs = args
+        .root("config")
+        .and_then(|config| config.get("templates"))
+        .and_then(|templates| templates.get("messages"));
+    let (locale, message) = i18n::locale_chain(args.locale())
+        .into_iter()
+        .find_map(|candidate| {
+            let message = catalogs
+                .and_then(|catalogs| catalogs.get(&candidate))
+                .and_then(|catalog| catalog.get(&key))
+                .and_then(JsonValue::as_str)
+                .map(str::to_string)
+                .or_else(|| i18n::lookup(&candidate, &key))?;
+            Some((candidate, message))
+        })
+        .unwrap_or_else(|| (args.locale().to_string(), key));
+    Ok(json!(i18n::format(&locale, &message, &named)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::helpers::helper_error_detail;
+    use crate::render::{RenderOptions, render_template};
+
+    fn render_in(locale: &str, template: &str) -> String {
+        let context = json!({
+            "locale": locale,
+            "name": "Ada",
+            "config": { "templates": { "messages": {
+                "en": {
+                    "greeting": "Hello {name}",
+                    "errors.unsupported_operation": "Nope: {operation}",
+                    "inbox": "{count, plural, one {# new message} other {# new messages}}"
+                },
+                "fr": {
+                    "greeting": "Bonjour {name}",
+                    "inbox": "{count, plural, one {# nouveau message} other {# nouveaux messages}}"
+                }
+            } } }
+        });
+        render_template(template, &context, RenderOptions::default())
+            .expect("render")
+            .text
+    }
+
+    #[test]
+    fn t() {
+        assert_eq!(render_in("en", "{{t \"greeting\" name=name}}"), "Hello Ada");
+        assert_eq!(
+            render_in("fr-FR", "{{t \"greeting\" name=name}}"),
+            "Bonjour Ada"
+        );
+        assert_eq!(
+            render_in("de", "{{t \"greeting\" name=\"Bo\"}}"),
+            "Hello Bo"
+        );
+        // Tenant entries override the bundle; other locales keep the bundle.
+        assert_eq!(
+            render_in(
+                "en",
+                "{{t \"errors.unsupported_operation\" operation=\"x\"}}"
+            ),
+            "Nope: x"
+        );
+        assert_eq!(render_in("fr", "{{t \"date.month.1\"}}"), "janvier");
+        assert_eq!(render_in("en", "{{t \"no.such.key\"}}"), "no.such.key");
+        assert_eq!(render_in("en", "{{t \"inbox\" count=1}}"), "1 new message");
+        assert_eq!(
+            render_in("fr", "{{t \"inbox\" count=0}}"),
+            "0 nouveau message"
+        );
+        assert_eq!(
+            render_in("fr", "{{t \"inbox\" count=3}}"),
+            "3 nouveaux messages"
+        );
+    }
+
+    #[test]
+    fn non_scalar_named_arguments_are_helper_errors() {
+        assert_eq!(
+            helper_error_detail("{{t \"greeting\" name=list}}", &json!({ "list": [1] })),
+            "`t` argument name must be a string, got an array"
+        );
+    }
+}
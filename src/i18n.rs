@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 use std::sync::OnceLock;
 
 use crate::i18n_bundle::{LocaleBundle, unpack_locales_from_cbor};
+use crate::message_format;
 
 // Generated by build.rs: static embedded CBOR translation bundle.
 include!(concat!(env!("OUT_DIR"), "/i18n_bundle.rs"));
@@ -15,7 +16,7 @@ fn bundle() -> &'static LocaleBundle {
 
 // Fallback precedence is deterministic:
 // exact locale -> base language -> en
-fn locale_chain(locale: &str) -> Vec<String> {
+pub fn locale_chain(locale: &str) -> Vec<String> {
     let normalized = locale.replace('_', "-");
     let mut chain = vec![normalized.clone()];
     if let Some((base, _)) = normalized.split_once('-') {
@@ -26,16 +27,48 @@ fn locale_chain(locale: &str) -> Vec<String> {
 }
 
 // Translation lookup function used throughout generated QA/setup code.
-// Extend by adding pluralization/context handling if your component needs it.
+// Returns the raw message; use `t_args` to fill placeholders.
 pub fn t(locale: &str, key: &str) -> String {
-    for candidate in locale_chain(locale) {
-        if let Some(map) = bundle().get(&candidate)
-            && let Some(value) = map.get(key)
-        {
-            return value.clone();
-        }
+    resolve(locale, key)
+        .map(|(_, message)| message)
+        .unwrap_or_else(|| key.to_string())
+}
+
+// `t` plus ICU MessageFormat arguments: `{name}` placeholders and
+// `plural` / `select` / `selectordinal`, with plural rules of the locale the
+// message was found in (so an English fallback uses English rules). Unknown
+// placeholders are kept verbatim so a stale translation never hides the raw
+// key structure.
+pub fn t_args(locale: &str, key: &str, args: &BTreeMap<String, String>) -> String {
+    match resolve(locale, key) {
+        Some((found, message)) => format(&found, &message, args),
+        None => key.to_string(),
     }
-    key.to_string()
+}
+
+// Message for `key` in exactly `locale`, without fallback. Callers layering
+// their own catalogs walk `locale_chain` themselves.
+pub fn lookup(locale: &str, key: &str) -> Option<String> {
+    bundle().get(locale)?.get(key).cloned()
+}
+
+// Formats an already resolved message for `locale`. A message that is not
+// valid MessageFormat still gets plain `{name}` substitution.
+pub fn format(locale: &str, message: &str, args: &BTreeMap<String, String>) -> String {
+    message_format::format(locale, message, args).unwrap_or_else(|_| {
+        let mut message = message.to_string();
+        for (name, value) in args {
+            message = message.replace(&format!("{{{name}}}"), value);
+        }
+        message
+    })
+}
+
+// First locale in the fallback chain that has `key`, with its message.
+fn resolve(locale: &str, key: &str) -> Option<(String, String)> {
+    locale_chain(locale)
+        .into_iter()
+        .find_map(|candidate| lookup(&candidate, key).map(|message| (candidate, message)))
 }
 
 // Returns canonical source key list (from `en`).
@@ -50,3 +83,52 @@ pub fn all_keys() -> Vec<String> {
 pub fn en_messages() -> BTreeMap<String, String> {
     bundle().get("en").cloned().unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_args_interpolates_placeholders_for_locale() {
+        let args = BTreeMap::from([
+            ("operation".to_string(), "shout".to_string()),
+            ("supported".to_string(), "text".to_string()),
+        ]);
+        assert_eq!(
+            t_args("en", "errors.unsupported_operation", &args),
+            "operation `shout` is not supported; use `text`"
+        );
+        assert!(t_args("fr-FR", "errors.unsupported_operation", &args).contains("`shout`"));
+    }
+
+    #[test]
+    fn t_args_formats_plurals_with_rules_of_the_resolved_locale() {
+        let args = |count: &str| BTreeMap::from([("count".to_string(), count.to_string())]);
+        let message = "{count, plural, one {# file} few {# files (few)} other {# files}}";
+        assert_eq!(format("en", message, &args("1")), "1 file");
+        assert_eq!(format("pl", message, &args("3")), "3 files (few)");
+        assert_eq!(format("en", "{broken", &args("1")), "{broken");
+        assert_eq!(format("en", "{count} '{", &args("1")), "1 {");
+    }
+
+    #[test]
+    fn every_bundled_message_is_valid_message_format() {
+        for (locale, messages) in bundle() {
+            for (key, message) in messages {
+                // Handlebars defaults are templates, not messages.
+                if message.contains("{{") {
+                    continue;
+                }
+                assert!(
+                    message_format::validate(message).is_ok(),
+                    "{locale}: {key} = {message}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn t_falls_back_to_key_when_unknown() {
+        assert_eq!(t("de", "errors.nope"), "errors.nope");
+    }
+}
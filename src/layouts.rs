@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use handlebars::Template;
+use handlebars::template::{Parameter, TemplateElement};
+
+use crate::diagnostics::{self, Diagnostic};
+
+// Layouts shipped with the component, available to every node as
+// `{{#> email}}...{{/email}}`. Config layouts (`templates.layouts`) with the
+// same name replace them.
+pub const BUILTIN: &[(&str, &str)] = &[("email", EMAIL), ("card", CARD)];
+
+// `{{> body}}` alone on a line is a standalone partial, so handlebars drops
+// the line break after it (partial files normally end in their own); the
+// blank line before the sign-off therefore takes three.
+const EMAIL: &str = "{{#> greeting}}{{t \"layout.email.greeting\"}}{{/greeting}}\n\n\
+    {{> body}}\n\n\n\
+    {{#> signoff}}{{t \"layout.email.signoff\"}}{{/signoff}}";
+
+const CARD: &str = "{{> title}}\n\n{{> body}}{{#> actions}}{{/actions}}";
+
+// A block a layout lets nodes fill: `{{> name}}` must be supplied,
+// `{{#> name}}default{{/name}}` falls back to its default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub name: String,
+    pub required: bool,
+}
+
+// Blocks declared by a compiled layout, in source order, without duplicates.
+pub fn blocks(layout: &Template) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    visit(layout, &mut |element| {
+        let (partial, required) = match element {
+            TemplateElement::PartialExpression(partial) => (partial, true),
+            TemplateElement::PartialBlock(partial) => (partial, false),
+            _ => return,
+        };
+        let Some(name) = partial.name.as_name().filter(|name| !name.starts_with('@')) else {
+            return;
+        };
+        match blocks.iter_mut().find(|block| block.name == name) {
+            Some(block) => block.required &= required,
+            None => blocks.push(Block {
+                name: name.to_string(),
+                required,
+            }),
+        }
+    });
+    blocks
+}
+
+// Checks every `{{#> layout}}` use in `template` against the layout's blocks:
+// required blocks the node neither overrides with `{{#*inline}}` nor finds
+// among `partials`, and overrides the layout does not declare. `layouts`
+// maps names to compiled templates; `partials` lists every name `{{> name}}`
+// can include.
+pub fn check(
+    source: &str,
+    template: &Template,
+    layouts: &BTreeMap<&str, &Template>,
+    partials: &[&str],
+) -> Vec<Diagnostic> {
+    let mut found = Vec::new();
+    visit(template, &mut |element| {
+        let TemplateElement::PartialBlock(usage) = element else {
+            return;
+        };
+        let Some((name, layout)) = usage
+            .name
+            .as_name()
+            .and_then(|name| Some((name, *layouts.get(name)?)))
+        else {
+            return;
+        };
+        let declared = blocks(layout);
+        let overrides = usage
+            .template
+            .as_ref()
+            .map(inline_names)
+            .unwrap_or_default();
+        for block in &declared {
+            if block.required
+                && !overrides.contains(&block.name.as_str())
+                && !partials.contains(&block.name.as_str())
+            {
+                found.push(diagnostics::undefined_block(source, name, &block.name));
+            }
+        }
+        let names: Vec<&str> = declared.iter().map(|block| block.name.as_str()).collect();
+        for block in overrides {
+            if !names.contains(&block) {
+                found.push(diagnostics::unknown_block(source, name, block, &names));
+            }
+        }
+    });
+    found
+}
+
+// Names of the `{{#*inline "name"}}` blocks anywhere in `template`.
+pub fn inline_names(template: &Template) -> Vec<&str> {
+    let mut names = Vec::new();
+    visit(template, &mut |element| {
+        if let TemplateElement::DecoratorBlock(decorator) = element
+            && decorator.name.as_name() == Some("inline")
+            && let Some(Parameter::Literal(name)) = decorator.params.first()
+            && let Some(name) = name.as_str()
+        {
+            names.push(name);
+        }
+    });
+    names
+}
+
+// Depth-first walk over every element, including block bodies and `else`
+// branches.
+fn visit<'a>(template: &'a Template, f: &mut impl FnMut(&'a TemplateElement)) {
+    for element in &template.elements {
+        f(element);
+        let nested = match element {
+            TemplateElement::HelperBlock(helper) => {
+                [helper.template.as_ref(), helper.inverse.as_ref()]
+            }
+            TemplateElement::DecoratorBlock(decorator)
+            | TemplateElement::PartialBlock(decorator) => [decorator.template.as_ref(), None],
+            _ => [None, None],
+        };
+        for template in nested.into_iter().flatten() {
+            visit(template, f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::render::{RenderError, RenderOptions, render_with_partials};
+
+    fn render(source: &str, partials: &[(&str, &str)]) -> Result<String, RenderError> {
+        let mut sources: BTreeMap<&str, &str> = BUILTIN.iter().copied().collect();
+        sources.extend(partials.iter().copied());
+        let context = json!({ "locale": "en", "name": "Ada", "order": "A-1" });
+        let hash = crate::cache::template_hash(source);
+        render_with_partials(source, &hash, &context, RenderOptions::default(), &sources)
+            .map(|rendered| rendered.text)
+    }
+
+    #[test]
+    fn builtin_layouts_fill_overridden_blocks() {
+        let email =
+            "{{#> email}}{{#*inline \"body\"}}Order {{order}} shipped.{{/inline}}{{/email}}";
+        assert_eq!(
+            render(email, &[]).expect("render"),
+            "Hello,\n\nOrder A-1 shipped.\n\nBest regards,"
+        );
+        let greeted = "{{#> email}}{{#*inline \"greeting\"}}Hi {{name}},{{/inline}}\
+            {{#*inline \"body\"}}Thanks!{{/inline}}{{/email}}";
+        assert_eq!(
+            render(greeted, &[]).expect("render"),
+            "Hi Ada,\n\nThanks!\n\nBest regards,"
+        );
+    }
+
+    #[test]
+    fn config_layouts_replace_builtins() {
+        let source = "{{#> card}}{{#*inline \"body\"}}{{name}}{{/inline}}{{/card}}";
+        assert_eq!(
+            render(source, &[("card", "[{{> body}}]")]).expect("render"),
+            "[Ada]"
+        );
+    }
+
+    #[test]
+    fn reports_blocks_missing_from_the_node_or_layout() {
+        let source = "{{#> card}}{{#*inline \"bdy\"}}x{{/inline}}{{/card}}";
+        let template = Template::compile(source).expect("compile");
+        let card = Template::compile(CARD).expect("compile");
+        let layouts = BTreeMap::from([("card", &card)]);
+        let found = check(source, &template, &layouts, &["title"]);
+        let codes: Vec<_> = found
+            .iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.token.as_deref()))
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                (diagnostics::CODE_UNDEFINED_BLOCK, Some("body")),
+                (diagnostics::CODE_UNKNOWN_BLOCK, Some("bdy"))
+            ]
+        );
+        assert_eq!(
+            found[1].fix.as_ref().map(|fix| &fix.args["suggestion"]),
+            Some(&"body".to_string())
+        );
+
+        let err = render(source, &[("title", "T")]).expect_err("undefined block");
+        let RenderError::Render(diagnostic) = err else {
+            panic!("expected render error");
+        };
+        assert_eq!(diagnostic.code, diagnostics::CODE_UNDEFINED_BLOCK);
+        assert_eq!(diagnostic.token.as_deref(), Some("body"));
+    }
+}
@@ -1,15 +1,13 @@
-#[cfg(target_arch = "wasm32")]
-use std::collections::BTreeMap;
-#[cfg(test)]
+#[cfg(any(target_arch = "wasm32", test))]
 use std::collections::BTreeMap;
 
 #[cfg(target_arch = "wasm32")]
 use greentic_types::cbor::canonical;
 #[cfg(any(target_arch = "wasm32", test))]
 use greentic_types::i18n_text::I18nText;
-#[cfg(target_arch = "wasm32")]
-use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
-#[cfg(target_arch = "wasm32")]
+#[cfg(any(target_arch = "wasm32", test))]
+use greentic_types::schemas::common::schema_ir::SchemaIr;
+#[cfg(any(target_arch = "wasm32", test))]
 use greentic_types::schemas::component::v0_6_0::{
     ComponentDescribe, ComponentInfo, ComponentOperation, ComponentRunInput, ComponentRunOutput,
     schema_hash,
@@ -32,9 +30,21 @@ use bindings::exports::greentic::component::{
     component_runtime, component_schema,
 };
 
+pub mod cache;
+pub mod config;
+pub mod context;
+pub mod diagnostics;
+pub mod helpers;
 pub mod i18n;
 pub mod i18n_bundle;
+pub mod layouts;
+pub mod message_format;
+pub mod number_bundle;
+pub mod plural;
 pub mod qa;
+pub mod render;
+pub mod runtime;
+pub mod schema;
 
 const COMPONENT_NAME: &str = "component-templates";
 const COMPONENT_ORG: &str = "ai.greentic";
@@ -77,16 +87,9 @@ impl component_schema::Guest for Component {
 #[cfg(target_arch = "wasm32")]
 impl component_runtime::Guest for Component {
     fn run(input: Vec<u8>, state: Vec<u8>) -> component_runtime::RunResult {
-        let value = parse_payload(&input);
-        let input_text = value
-            .get("input")
-            .and_then(|v| v.as_str())
-            .map(ToOwned::to_owned)
-            .unwrap_or_else(|| value.to_string());
-
-        let output = serde_json::json!({
-            "message": handle_message("handle_message", &input_text)
-        });
+        let invocation = parse_payload(&input);
+        let current_state = parse_state(&state);
+        let output = runtime::run_to_output(&invocation, &current_state);
 
         component_runtime::RunResult {
             output: encode_cbor(&output),
@@ -102,11 +105,15 @@ impl component_qa::Guest for Component {
         encode_cbor(&qa_spec_payload(mode_key))
     }
 
+    // Always the `{ ok, config, warnings, errors, meta }` envelope; `config`
+    // is only present when `ok` is true.
     fn apply_answers(mode: QaMode, current_config: Vec<u8>, answers: Vec<u8>) -> Vec<u8> {
-        let _ = mode;
-        let updated =
-            apply_template_answers(parse_payload(&current_config), parse_payload(&answers));
-        encode_cbor(&updated)
+        let mode = qa::normalize_mode(mode_key(mode)).unwrap_or(qa::NormalizedMode::Setup);
+        encode_cbor(&apply_template_answers_checked(
+            mode,
+            parse_payload(&current_config),
+            parse_payload(&answers),
+        ))
     }
 }
 
@@ -153,15 +160,28 @@ fn qa_spec_payload(mode_key: &str) -> ComponentQaSpec {
     let asks_template_text = matches!(mode_key, "default" | "setup" | "update");
     let required = matches!(mode_key, "default" | "setup");
     let questions = if asks_template_text {
-        vec![Question {
-            id: "templates.text".to_string(),
-            label: I18nText::new("qa.text.label", None),
-            help: None,
-            error: None,
-            kind: QuestionKind::Text,
-            required,
-            default: None,
-        }]
+        vec![
+            Question {
+                id: "templates.text".to_string(),
+                label: I18nText::new("qa.text.label", None),
+                help: None,
+                error: None,
+                kind: QuestionKind::Text,
+                required,
+                default: None,
+            },
+            // A JSON object of locale -> template, merged into the current
+            // variants; an empty template removes that locale.
+            Question {
+                id: "templates.text_i18n".to_string(),
+                label: I18nText::new("qa.text_i18n.label", None),
+                help: Some(I18nText::new("qa.text_i18n.help", None)),
+                error: None,
+                kind: QuestionKind::Text,
+                required: false,
+                default: None,
+            },
+        ]
     } else {
         Vec::new()
     };
@@ -208,6 +228,25 @@ fn extract_template_text_answer(answers: &serde_json::Value) -> Option<String> {
         .map(ToOwned::to_owned)
 }
 
+// Locale variants answered as an object or, from the text question, as the
+// JSON text of one. Anything else is kept as given so validation reports it.
+#[cfg(any(target_arch = "wasm32", test))]
+fn extract_template_variants_answer(answers: &serde_json::Value) -> Option<serde_json::Value> {
+    let map = answers.as_object()?;
+    let value = map
+        .get("text_i18n")
+        .or_else(|| map.get("templates.text_i18n"))
+        .or_else(|| answers.pointer("/config/templates/text_i18n"))
+        .or_else(|| answers.pointer("/templates/text_i18n"))?;
+    match value {
+        serde_json::Value::String(text) if text.trim().is_empty() => None,
+        serde_json::Value::String(text) => {
+            Some(serde_json::from_str(text).unwrap_or_else(|_| value.clone()))
+        }
+        _ => Some(value.clone()),
+    }
+}
+
 #[cfg(any(target_arch = "wasm32", test))]
 fn apply_template_answers(
     current_config: serde_json::Value,
@@ -233,16 +272,250 @@ fn apply_template_answers(
             Some(serde_json::Value::Object(map)) => map,
             _ => serde_json::Map::new(),
         };
+        let text = config::normalize_template(&text);
+        templates.insert(
+            "template_hash".to_string(),
+            serde_json::Value::String(cache::template_hash(&text)),
+        );
         templates.insert("text".to_string(), serde_json::Value::String(text));
         config.insert(
             "templates".to_string(),
             serde_json::Value::Object(templates),
         );
     }
+    if let Some(variants) = extract_template_variants_answer(&answers) {
+        let mut templates = match config.remove("templates") {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        let merged = match (templates.remove("text_i18n"), variants) {
+            (current, serde_json::Value::Object(variants)) => {
+                let mut merged = match current {
+                    Some(serde_json::Value::Object(map)) => map,
+                    _ => serde_json::Map::new(),
+                };
+                for (locale, text) in variants {
+                    match text.as_str() {
+                        Some(text) if text.trim().is_empty() => {
+                            merged.remove(&locale);
+                        }
+                        Some(text) => {
+                            let text = config::normalize_template(text);
+                            merged.insert(locale, serde_json::Value::String(text));
+                        }
+                        None => {
+                            merged.insert(locale, text);
+                        }
+                    }
+                }
+                serde_json::Value::Object(merged)
+            }
+            (_, invalid) => invalid,
+        };
+        templates.insert("text_i18n".to_string(), merged);
+        config.insert(
+            "templates".to_string(),
+            serde_json::Value::Object(templates),
+        );
+    }
 
     serde_json::Value::Object(config)
 }
 
+// `apply_template_answers` plus configuration-time validation, reported in the
+// `{ ok, config, warnings, errors, meta }` shape of `qa::apply_answers`.
+// Valid templates are compiled into the cache so the first run is warm.
+#[cfg(any(target_arch = "wasm32", test))]
+fn apply_template_answers_checked(
+    mode: qa::NormalizedMode,
+    current_config: serde_json::Value,
+    answers: serde_json::Value,
+) -> serde_json::Value {
+    let locale = answers
+        .get("locale")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("en")
+        .to_string();
+    let config = apply_template_answers(current_config, answers);
+    let errors = template_config_errors(&config, &locale);
+    let meta = serde_json::json!({ "mode": mode.as_str(), "version": "v1" });
+    if !errors.is_empty() {
+        return serde_json::json!({
+            "ok": false,
+            "warnings": [],
+            "errors": errors,
+            "meta": meta
+        });
+    }
+    serde_json::json!({
+        "ok": true,
+        "config": config,
+        "warnings": [],
+        "errors": [],
+        "meta": meta
+    })
+}
+
+// Located, localized diagnostics ride along with each syntax error so authors
+// see the line, caret and suggested fix in the QA surface.
+#[cfg(any(target_arch = "wasm32", test))]
+fn template_config_errors(config: &serde_json::Value, locale: &str) -> Vec<serde_json::Value> {
+    let Some(templates) = config.get("templates") else {
+        return Vec::new();
+    };
+    let mut errors = Vec::new();
+    let mut error =
+        |key: &str, field: String, message: String, diagnostics: &[diagnostics::Diagnostic]| {
+            let diagnostics: Vec<_> = diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.to_json(locale))
+                .collect();
+            errors.push(serde_json::json!({
+                "key": key,
+                "msg_key": key,
+                "fields": [field],
+                "message": message,
+                "diagnostics": diagnostics
+            }));
+        };
+
+    let templates = match config::TemplatesConfig::from_value(templates) {
+        Ok(templates) => templates,
+        Err(message) => {
+            error(
+                "qa.error.invalid_config",
+                "templates".to_string(),
+                message,
+                &[],
+            );
+            return errors;
+        }
+    };
+    let texts = std::iter::once(("templates.text".to_string(), &templates.text)).chain(
+        templates
+            .text_i18n
+            .iter()
+            .map(|(locale, text)| (format!("templates.text_i18n.{locale}"), text)),
+    );
+    let mut compiled_texts = Vec::new();
+    for (field, text) in texts {
+        match cache::compiled_template(text, render::compile) {
+            Ok(template) => compiled_texts.push((field, text, template)),
+            Err(err) => error(
+                "qa.error.template_syntax",
+                field,
+                err.to_string(),
+                err.diagnostics(),
+            ),
+        }
+    }
+    for locale in templates.text_i18n.keys() {
+        if locale.trim().is_empty() || locale.contains(char::is_whitespace) {
+            error(
+                "qa.error.invalid_config",
+                format!("templates.text_i18n.{locale}"),
+                format!("`{locale}` is not a locale"),
+                &[],
+            );
+        }
+    }
+    if let Err(message) = templates.check_wrap() {
+        error(
+            "qa.error.invalid_config",
+            "templates.wrap".to_string(),
+            message,
+            &[],
+        );
+    }
+    if let Err(message) = templates.output_segments() {
+        error(
+            "qa.error.invalid_config",
+            "templates.output_path".to_string(),
+            message,
+            &[],
+        );
+    }
+    for (index, route) in templates.routes.iter().enumerate() {
+        // Bare expressions are wrapped in `#if` at runtime; check that form.
+        let condition = if route.when.contains("{{") {
+            route.when.clone()
+        } else {
+            format!("{{{{#if {}}}}}1{{{{/if}}}}", route.when)
+        };
+        if let Err(err) = render::compile(&condition) {
+            error(
+                "qa.error.template_syntax",
+                format!("templates.routes[{index}].when"),
+                err.to_string(),
+                err.diagnostics(),
+            );
+        }
+    }
+    let named = templates
+        .partials
+        .iter()
+        .map(|entry| ("partials", entry))
+        .chain(templates.layouts.iter().map(|entry| ("layouts", entry)));
+    for (kind, (name, partial)) in named {
+        let field = format!("templates.{kind}.{name}");
+        if name.is_empty() || name.contains(|ch: char| ch.is_whitespace() || "{}".contains(ch)) {
+            error(
+                "qa.error.invalid_config",
+                field,
+                format!("name `{name}` cannot be used as `{{{{> name}}}}`"),
+                &[],
+            );
+        } else if let Err(err) = render::compiled_partial(partial) {
+            error(
+                "qa.error.template_syntax",
+                field,
+                err.to_string(),
+                err.diagnostics(),
+            );
+        }
+    }
+    // Layout blocks are checked against whatever compiled; broken layouts
+    // were reported above.
+    let sources = templates.partial_sources();
+    let compiled: Vec<_> = sources
+        .iter()
+        .filter_map(|(name, source)| Some((*name, render::compile(source).ok()?)))
+        .collect();
+    let layouts = compiled
+        .iter()
+        .map(|(name, layout)| (*name, layout))
+        .collect();
+    let names: Vec<&str> = sources.keys().copied().collect();
+    for (field, text, template) in compiled_texts {
+        let found = layouts::check(text, &template, &layouts, &names);
+        if !found.is_empty() {
+            let details: Vec<_> = found
+                .iter()
+                .map(|diagnostic| diagnostic.detail.as_str())
+                .collect();
+            error(
+                "qa.error.template_blocks",
+                field,
+                details.join("; "),
+                &found,
+            );
+        }
+    }
+    for (catalog, messages) in &templates.messages {
+        for (key, message) in messages {
+            if let Err(err) = message_format::validate(message) {
+                error(
+                    "qa.error.invalid_config",
+                    format!("templates.messages.{catalog}.{key}"),
+                    err.to_string(),
+                    &[],
+                );
+            }
+        }
+    }
+    errors
+}
+
 #[cfg(target_arch = "wasm32")]
 fn encode_cbor<T: serde::Serialize>(value: &T) -> Vec<u8> {
     canonical::to_canonical_cbor_allow_floats(value).expect("encode cbor")
@@ -250,12 +523,21 @@ fn encode_cbor<T: serde::Serialize>(value: &T) -> Vec<u8> {
 
 #[cfg(target_arch = "wasm32")]
 fn parse_payload(input: &[u8]) -> serde_json::Value {
-    if let Ok(value) = canonical::from_cbor(input) {
+    if let Some(value) = context::from_cbor(input) {
         return value;
     }
     serde_json::from_slice(input).unwrap_or_else(|_| serde_json::json!({}))
 }
 
+// Node state is optional; an empty buffer means no prior state.
+#[cfg(target_arch = "wasm32")]
+fn parse_state(state: &[u8]) -> serde_json::Value {
+    if state.is_empty() {
+        return serde_json::Value::Null;
+    }
+    parse_payload(state)
+}
+
 #[cfg(target_arch = "wasm32")]
 fn mode_key(mode: QaMode) -> &'static str {
     match mode {
@@ -266,65 +548,7 @@ fn mode_key(mode: QaMode) -> &'static str {
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-fn input_schema() -> SchemaIr {
-    SchemaIr::Object {
-        properties: BTreeMap::from([(
-            "input".to_string(),
-            SchemaIr::String {
-                min_len: Some(0),
-                max_len: None,
-                regex: None,
-                format: None,
-            },
-        )]),
-        required: vec!["input".to_string()],
-        additional: AdditionalProperties::Allow,
-    }
-}
-
-#[cfg(target_arch = "wasm32")]
-fn output_schema() -> SchemaIr {
-    SchemaIr::Object {
-        properties: BTreeMap::from([(
-            "message".to_string(),
-            SchemaIr::String {
-                min_len: Some(0),
-                max_len: None,
-                regex: None,
-                format: None,
-            },
-        )]),
-        required: vec!["message".to_string()],
-        additional: AdditionalProperties::Allow,
-    }
-}
-
-#[cfg(target_arch = "wasm32")]
-fn config_schema() -> SchemaIr {
-    SchemaIr::Object {
-        properties: BTreeMap::from([(
-            "templates".to_string(),
-            SchemaIr::Object {
-                properties: BTreeMap::from([(
-                    "text".to_string(),
-                    SchemaIr::String {
-                        min_len: Some(0),
-                        max_len: None,
-                        regex: None,
-                        format: None,
-                    },
-                )]),
-                required: vec!["text".to_string()],
-                additional: AdditionalProperties::Allow,
-            },
-        )]),
-        required: Vec::new(),
-        additional: AdditionalProperties::Allow,
-    }
-}
-
-#[cfg(target_arch = "wasm32")]
+#[cfg(any(target_arch = "wasm32", test))]
 fn component_info() -> ComponentInfo {
     ComponentInfo {
         id: format!("{COMPONENT_ORG}.{COMPONENT_NAME}"),
@@ -337,28 +561,69 @@ fn component_info() -> ComponentInfo {
     }
 }
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(any(target_arch = "wasm32", test))]
+fn component_operation(
+    id: &str,
+    display_key: &str,
+    input: SchemaIr,
+    output: SchemaIr,
+    config: &SchemaIr,
+) -> ComponentOperation {
+    let op_schema_hash = schema_hash(&input, &output, config).unwrap_or_default();
+    ComponentOperation {
+        id: id.to_string(),
+        display_name: Some(I18nText::new(display_key, None)),
+        input: ComponentRunInput { schema: input },
+        output: ComponentRunOutput { schema: output },
+        defaults: BTreeMap::new(),
+        redactions: Vec::new(),
+        constraints: BTreeMap::new(),
+        schema_hash: op_schema_hash,
+    }
+}
+
+// Output ports for flow editors. Nodes always expose the default port; extra
+// ports come from the node's own `templates.routing` and `templates.routes`.
+#[cfg(any(target_arch = "wasm32", test))]
+fn describe_metadata() -> BTreeMap<String, ciborium::value::Value> {
+    let routing = serde_json::json!({
+        "default": config::DEFAULT_ROUTE,
+        "ports": [config::DEFAULT_ROUTE, config::ERROR_ROUTE],
+        "error_port": config::ERROR_ROUTE,
+        "configured_by": ["templates.routing", "templates.routes[].to"],
+        "output": "control.routing"
+    });
+    let value =
+        ciborium::value::Value::serialized(&routing).unwrap_or(ciborium::value::Value::Null);
+    BTreeMap::from([("routing".to_string(), value)])
+}
+
+#[cfg(any(target_arch = "wasm32", test))]
 fn component_describe() -> ComponentDescribe {
-    let input = input_schema();
-    let output = output_schema();
-    let config = config_schema();
-    let op_schema_hash = schema_hash(&input, &output, &config).unwrap_or_default();
+    let config = schema::config_schema();
 
     ComponentDescribe {
         info: component_info(),
         provided_capabilities: Vec::new(),
         required_capabilities: Vec::new(),
-        metadata: BTreeMap::new(),
-        operations: vec![ComponentOperation {
-            id: "handle_message".to_string(),
-            display_name: Some(I18nText::new("component.operation.handle_message", None)),
-            input: ComponentRunInput { schema: input },
-            output: ComponentRunOutput { schema: output },
-            defaults: BTreeMap::new(),
-            redactions: Vec::new(),
-            constraints: BTreeMap::new(),
-            schema_hash: op_schema_hash,
-        }],
+        metadata: describe_metadata(),
+        operations: vec![
+            component_operation(
+                runtime::OP_TEXT,
+                "component.operation.text",
+                schema::text_input_schema(),
+                schema::text_output_schema(config::DEFAULT_OUTPUT_PATH, config::DEFAULT_WRAP),
+                &config,
+            ),
+            // Compatibility alias kept for flows generated before `text` existed.
+            component_operation(
+                runtime::OP_HANDLE_MESSAGE,
+                "component.operation.handle_message",
+                schema::handle_message_input_schema(),
+                schema::handle_message_output_schema(),
+                &config,
+            ),
+        ],
         config_schema: config,
     }
 }
@@ -375,17 +640,20 @@ fn component_describe_cbor() -> Vec<u8> {
 
 #[cfg(target_arch = "wasm32")]
 fn input_schema_cbor() -> Vec<u8> {
-    encode_cbor(&input_schema())
+    encode_cbor(&schema::text_input_schema())
 }
 
 #[cfg(target_arch = "wasm32")]
 fn output_schema_cbor() -> Vec<u8> {
-    encode_cbor(&output_schema())
+    encode_cbor(&schema::text_output_schema(
+        config::DEFAULT_OUTPUT_PATH,
+        config::DEFAULT_WRAP,
+    ))
 }
 
 #[cfg(target_arch = "wasm32")]
 fn config_schema_cbor() -> Vec<u8> {
-    encode_cbor(&config_schema())
+    encode_cbor(&schema::config_schema())
 }
 
 #[cfg(test)]
@@ -407,12 +675,46 @@ mod tests {
         assert!(body.contains("demo"));
     }
 
+    #[test]
+    fn describe_exposes_text_operation_and_handle_message_alias() {
+        let describe = component_describe();
+        let ids: Vec<&str> = describe
+            .operations
+            .iter()
+            .map(|op| op.id.as_str())
+            .collect();
+        assert_eq!(ids, ["text", "handle_message"]);
+
+        let text = &describe.operations[0];
+        let alias = &describe.operations[1];
+        assert_eq!(
+            text.display_name.as_ref().map(|name| name.key.as_str()),
+            Some("component.operation.text")
+        );
+        assert!(!text.schema_hash.is_empty());
+        assert_ne!(text.schema_hash, alias.schema_hash);
+    }
+
+    #[test]
+    fn describe_metadata_declares_default_port() {
+        let describe = component_describe();
+        let routing: serde_json::Value = describe.metadata["routing"]
+            .deserialized()
+            .expect("routing metadata");
+        assert_eq!(routing["default"], "out");
+        assert_eq!(routing["ports"], serde_json::json!(["out", "error"]));
+        assert_eq!(routing["error_port"], "error");
+    }
+
     #[test]
     fn qa_spec_default_includes_text_question() {
         let spec = qa_spec_payload("default");
         let first = spec.questions.first().expect("text question");
         assert_eq!(first.id, "templates.text");
         assert_eq!(first.label.key, "qa.text.label");
+        let variants = &spec.questions[1];
+        assert_eq!(variants.id, "templates.text_i18n");
+        assert!(!variants.required);
     }
 
     #[test]
@@ -442,6 +744,189 @@ mod tests {
         let updated = apply_template_answers(current, answers);
         assert_eq!(updated["templates"]["text"], "Hi {{name}}");
         assert_eq!(updated["templates"]["output_path"], "text");
+        assert_eq!(
+            updated["templates"]["template_hash"],
+            cache::template_hash("Hi {{name}}")
+        );
+    }
+
+    #[test]
+    fn apply_answers_stores_normalized_text_and_hash() {
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Setup,
+            serde_json::json!({}),
+            serde_json::json!({ "text": "Hi\r\n{{payload.name}}" }),
+        );
+        assert_eq!(result["ok"], true);
+        let templates = &result["config"]["templates"];
+        assert_eq!(templates["text"], "Hi\n{{payload.name}}");
+        assert_eq!(
+            templates["template_hash"],
+            cache::template_hash("Hi\n{{payload.name}}")
+        );
+    }
+
+    #[test]
+    fn apply_answers_merges_locale_variants() {
+        let current = serde_json::json!({
+            "templates": {
+                "text": "Hi {{name}}",
+                "text_i18n": { "fr": "Salut {{name}}", "de": "Hallo {{name}}" }
+            }
+        });
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            current.clone(),
+            serde_json::json!({
+                "templates.text_i18n": "{\"fr\": \"Bonjour\\r\\n{{name}}\", \"de\": \"\", \"es\": \"Hola {{name}}\"}"
+            }),
+        );
+        assert_eq!(result["ok"], true);
+        assert_eq!(
+            result["config"]["templates"]["text_i18n"],
+            serde_json::json!({ "es": "Hola {{name}}", "fr": "Bonjour\n{{name}}" })
+        );
+
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            current,
+            serde_json::json!({ "text_i18n": { "nl": "Hoi {{#if name}}" } }),
+        );
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["errors"][0]["key"], "qa.error.template_syntax");
+        assert_eq!(result["errors"][0]["fields"][0], "templates.text_i18n.nl");
+    }
+
+    #[test]
+    fn apply_answers_rejects_reserved_output_paths() {
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            serde_json::json!({ "templates": { "output_path": "control" } }),
+            serde_json::json!({ "text": "hi" }),
+        );
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["errors"][0]["key"], "qa.error.invalid_config");
+        assert_eq!(result["errors"][0]["fields"][0], "templates.output_path");
+    }
+
+    #[test]
+    fn apply_answers_rejects_routes_on_unwrapped_output() {
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            serde_json::json!({ "templates": { "wrap": false, "routing": "done" } }),
+            serde_json::json!({ "text": "hi" }),
+        );
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["errors"][0]["fields"][0], "templates.wrap");
+    }
+
+    #[test]
+    fn apply_answers_rejects_template_syntax_errors() {
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            serde_json::json!({
+                "templates": { "routes": [{ "when": "{{#if}}", "to": "vip" }] }
+            }),
+            serde_json::json!({ "text": "Hello {{#if payload.vip}}" }),
+        );
+        assert_eq!(result["ok"], false);
+        assert!(result.get("config").is_none());
+        assert_eq!(result["meta"]["mode"], "update");
+        let errors = result["errors"].as_array().expect("errors");
+        let fields: Vec<_> = errors.iter().map(|error| &error["fields"][0]).collect();
+        assert_eq!(fields, ["templates.text", "templates.routes[0].when"]);
+        assert!(
+            errors
+                .iter()
+                .all(|error| error["key"] == "qa.error.template_syntax")
+        );
+        let diagnostic = &errors[0]["diagnostics"][0];
+        assert_eq!(diagnostic["code"], "template_syntax");
+        assert_eq!(diagnostic["token"], "{{#if payload.vip}}");
+        assert_eq!(
+            diagnostic["fix"]["message"],
+            "Close the block with `{{/if}}`."
+        );
+    }
+
+    #[test]
+    fn apply_answers_rejects_malformed_catalog_messages() {
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            serde_json::json!({
+                "templates": { "messages": { "en": {
+                    "ok": "{count, plural, one {# item} other {# items}}",
+                    "broken": "{count, plural, one {# item}}"
+                } } }
+            }),
+            serde_json::json!({ "text": "{{t \"ok\" count=1}}" }),
+        );
+        assert_eq!(result["ok"], false);
+        let errors = result["errors"].as_array().expect("errors");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["key"], "qa.error.invalid_config");
+        assert_eq!(errors[0]["fields"][0], "templates.messages.en.broken");
+        assert_eq!(
+            errors[0]["message"],
+            "missing `other` branch at character 29"
+        );
+    }
+
+    #[test]
+    fn apply_answers_rejects_invalid_partials() {
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            serde_json::json!({
+                "templates": { "partials": {
+                    "signature": "-- {{team}}",
+                    "footer": "{{#if vip}}VIP",
+                    "two words": "x"
+                } }
+            }),
+            serde_json::json!({ "text": "{{> signature team=\"Support\"}}" }),
+        );
+        assert_eq!(result["ok"], false);
+        let errors = result["errors"].as_array().expect("errors");
+        let fields: Vec<_> = errors.iter().map(|error| &error["fields"][0]).collect();
+        assert_eq!(
+            fields,
+            ["templates.partials.footer", "templates.partials.two words"]
+        );
+        assert_eq!(errors[0]["key"], "qa.error.template_syntax");
+        assert_eq!(errors[0]["diagnostics"][0]["token"], "{{#if vip}}");
+        assert_eq!(errors[1]["key"], "qa.error.invalid_config");
+    }
+
+    #[test]
+    fn apply_answers_reports_undefined_layout_blocks() {
+        let result = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            serde_json::json!({
+                "templates": { "layouts": { "receipt": "{{> lines}}\n{{#> total}}-{{/total}}" } }
+            }),
+            serde_json::json!({
+                "text": "{{#> receipt}}{{#*inline \"totl\"}}{{sum}}{{/inline}}{{/receipt}}"
+            }),
+        );
+        assert_eq!(result["ok"], false);
+        let errors = result["errors"].as_array().expect("errors");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["key"], "qa.error.template_blocks");
+        assert_eq!(errors[0]["fields"][0], "templates.text");
+        let diagnostics = errors[0]["diagnostics"].as_array().expect("diagnostics");
+        assert_eq!(diagnostics[0]["code"], "undefined_block");
+        assert_eq!(diagnostics[0]["token"], "lines");
+        assert_eq!(diagnostics[1]["code"], "unknown_block");
+        assert_eq!(diagnostics[1]["fix"]["message"], "Did you mean `total`?");
+
+        let fixed = apply_template_answers_checked(
+            qa::NormalizedMode::Update,
+            serde_json::json!({}),
+            serde_json::json!({
+                "text": "{{#> email}}{{#*inline \"body\"}}Hi{{/inline}}{{/email}}"
+            }),
+        );
+        assert_eq!(fixed["ok"], true);
     }
 
     #[test]
@@ -0,0 +1,478 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::plural::{self, Operands};
+
+// ICU MessageFormat subset used by translations and tenant catalogs:
+//
+// - `{name}` interpolation (also `{name, number}` and other simple types,
+//   which render the argument as given)
+// - `{count, plural, [offset:N] =0 {none} one {# item} other {# items}}`
+// - `{place, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}`
+// - `{gender, select, female {she} male {he} other {they}}`
+//
+// `#` inside a plural branch is the (offset-adjusted) number as written.
+// Apostrophes quote only before a syntax character (`'{'`, `'#'`), and
+// `''` is a literal apostrophe, so "don't" needs no escaping.
+
+// Nesting limit for plural/select branches.
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    // Char offset into the message.
+    pub position: usize,
+    pub problem: &'static str,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at character {}", self.problem, self.position)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Argument(String),
+    // `#` in a plural branch.
+    Number,
+    Plural {
+        name: String,
+        ordinal: bool,
+        offset: f64,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+    Select {
+        name: String,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+}
+
+// Formats `message` for `locale`, whose plural rules pick the branches.
+// Arguments missing from `args` keep their `{name}` placeholder; plural and
+// select fall back to their `other` branch.
+pub fn format(
+    locale: &str,
+    message: &str,
+    args: &BTreeMap<String, String>,
+) -> Result<String, FormatError> {
+    let parts = parse(message)?;
+    let mut out = String::with_capacity(message.len());
+    write_parts(&parts, locale, args, None, &mut out);
+    Ok(out)
+}
+
+// Checks `message` without formatting it, e.g. when validating catalogs.
+pub fn validate(message: &str) -> Result<(), FormatError> {
+    parse(message).map(drop)
+}
+
+fn parse(message: &str) -> Result<Vec<Part>, FormatError> {
+    let mut parser = Parser {
+        chars: message.chars().collect(),
+        position: 0,
+    };
+    let parts = parser.message(0, false)?;
+    if parser.position < parser.chars.len() {
+        return Err(parser.error("unmatched `}`"));
+    }
+    Ok(parts)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn error(&self, problem: &'static str) -> FormatError {
+        FormatError {
+            position: self.position,
+            problem,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    // Text and arguments up to an unconsumed `}` or the end of input.
+    fn message(&mut self, depth: usize, in_plural: bool) -> Result<Vec<Part>, FormatError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        while let Some(ch) = self.peek() {
+            match ch {
+                '}' => break,
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(self.argument(depth, in_plural)?);
+                }
+                '#' if in_plural => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Number);
+                    self.position += 1;
+                }
+                '\'' => self.apostrophe(in_plural, &mut text),
+                _ => {
+                    text.push(ch);
+                    self.position += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    fn apostrophe(&mut self, in_plural: bool, text: &mut String) {
+        self.position += 1;
+        match self.peek() {
+            Some('\'') => {
+                text.push('\'');
+                self.position += 1;
+            }
+            Some('{' | '}' | '|') => self.quoted(text),
+            Some('#') if in_plural => self.quoted(text),
+            _ => text.push('\''),
+        }
+    }
+
+    // Literal text up to the closing apostrophe (or the end of input).
+    fn quoted(&mut self, text: &mut String) {
+        while let Some(ch) = self.peek() {
+            self.position += 1;
+            if ch != '\'' {
+                text.push(ch);
+            } else if self.peek() == Some('\'') {
+                text.push('\'');
+                self.position += 1;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn argument(&mut self, depth: usize, in_plural: bool) -> Result<Part, FormatError> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error("message nests too deeply"));
+        }
+        self.position += 1;
+        self.skip_whitespace();
+        let name = self.word();
+        if name.is_empty() {
+            return Err(self.error("expected an argument name"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some('}') => {
+                self.position += 1;
+                return Ok(Part::Argument(name));
+            }
+            Some(',') => self.position += 1,
+            _ => return Err(self.error("expected `,` or `}` after the argument name")),
+        }
+        self.skip_whitespace();
+        let kind = self.word();
+        self.skip_whitespace();
+        match kind.as_str() {
+            "plural" | "selectordinal" | "select" => {}
+            "" => return Err(self.error("expected an argument type")),
+            // `{n, number}`, `{d, date, short}`, ...: rendered as given.
+            _ => {
+                self.skip_style()?;
+                return Ok(Part::Argument(name));
+            }
+        }
+        if self.peek() != Some(',') {
+            return Err(self.error("expected `,` after the argument type"));
+        }
+        self.position += 1;
+        self.skip_whitespace();
+        let plural = kind != "select";
+        let mut offset = 0.0;
+        if plural && self.chars[self.position..].starts_with(&['o', 'f', 'f', 's', 'e', 't', ':']) {
+            self.position += 7;
+            self.skip_whitespace();
+            offset = self
+                .word()
+                .parse::<f64>()
+                .map_err(|_| self.error("expected a number after `offset:`"))?;
+        }
+        let cases = self.cases(depth, plural || in_plural, plural)?;
+        Ok(if plural {
+            Part::Plural {
+                name,
+                ordinal: kind == "selectordinal",
+                offset,
+                cases,
+            }
+        } else {
+            Part::Select { name, cases }
+        })
+    }
+
+    // `key {message}` pairs up to and including the closing `}`.
+    fn cases(
+        &mut self,
+        depth: usize,
+        in_plural: bool,
+        plural: bool,
+    ) -> Result<Vec<(String, Vec<Part>)>, FormatError> {
+        let mut cases = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('}') => {
+                    self.position += 1;
+                    break;
+                }
+                None => return Err(self.error("unclosed argument")),
+                _ => {}
+            }
+            let key = self.word();
+            if key.is_empty() {
+                return Err(self.error("expected a selector"));
+            }
+            if plural && key.starts_with('=') && key[1..].parse::<f64>().is_err() {
+                return Err(self.error("expected a number after `=`"));
+            }
+            self.skip_whitespace();
+            if self.peek() != Some('{') {
+                return Err(self.error("expected `{` after the selector"));
+            }
+            self.position += 1;
+            let branch = self.message(depth + 1, in_plural)?;
+            if self.peek() != Some('}') {
+                return Err(self.error("unclosed branch"));
+            }
+            self.position += 1;
+            cases.push((key, branch));
+        }
+        if !cases.iter().any(|(key, _)| key == "other") {
+            return Err(self.error("missing `other` branch"));
+        }
+        Ok(cases)
+    }
+
+    // Skips a simple argument's style (`, short`) up to the closing `}`.
+    fn skip_style(&mut self) -> Result<(), FormatError> {
+        let mut depth = 0usize;
+        while let Some(ch) = self.peek() {
+            self.position += 1;
+            match ch {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(()),
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        Err(self.error("unclosed argument"))
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|ch| !ch.is_whitespace() && !matches!(ch, ',' | '{' | '}'))
+        {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+}
+
+fn write_parts(
+    parts: &[Part],
+    locale: &str,
+    args: &BTreeMap<String, String>,
+    number: Option<&str>,
+    out: &mut String,
+) {
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Argument(name) => match args.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    out.push('{');
+                    out.push_str(name);
+                    out.push('}');
+                }
+            },
+            Part::Number => out.push_str(number.unwrap_or("#")),
+            Part::Plural {
+                name,
+                ordinal,
+                offset,
+                cases,
+            } => {
+                let value = args.get(name).map(String::as_str);
+                let (branch, shown) = plural_branch(locale, value, *ordinal, *offset, cases);
+                write_parts(branch, locale, args, shown.as_deref(), out);
+            }
+            Part::Select { name, cases } => {
+                let value = args.get(name).map(String::as_str).unwrap_or_default();
+                let branch = cases
+                    .iter()
+                    .find(|(key, _)| key == value)
+                    .or_else(|| cases.iter().find(|(key, _)| key == "other"))
+                    .map(|(_, branch)| branch.as_slice())
+                    .unwrap_or_default();
+                write_parts(branch, locale, args, number, out);
+            }
+        }
+    }
+}
+
+// Exact `=N` selectors match the raw value; keywords use the value minus
+// `offset`, which is also what `#` shows.
+fn plural_branch<'p>(
+    locale: &str,
+    value: Option<&str>,
+    ordinal: bool,
+    offset: f64,
+    cases: &'p [(String, Vec<Part>)],
+) -> (&'p [Part], Option<String>) {
+    let branch = |key: &str| {
+        cases
+            .iter()
+            .find(|(case, _)| case == key)
+            .map(|(_, branch)| branch.as_slice())
+    };
+    let other = branch("other").unwrap_or_default();
+    let Some(raw) = value.map(str::trim) else {
+        return (other, None);
+    };
+    let Some(number) = raw.parse::<f64>().ok().filter(|number| number.is_finite()) else {
+        return (other, Some(raw.to_string()));
+    };
+    let exact = cases.iter().find(|(key, _)| {
+        key.strip_prefix('=')
+            .and_then(|exact| exact.parse::<f64>().ok())
+            == Some(number)
+    });
+    let shown = if offset == 0.0 {
+        raw.to_string()
+    } else {
+        (number - offset).to_string()
+    };
+    if let Some((_, branch)) = exact {
+        return (branch, Some(shown));
+    }
+    let category = Operands::parse(&shown).map(|ops| {
+        if ordinal {
+            plural::ordinal(locale, &ops)
+        } else {
+            plural::cardinal(locale, &ops)
+        }
+    });
+    let chosen = category
+        .and_then(|category| branch(category.as_str()))
+        .unwrap_or(other);
+    (chosen, Some(shown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(locale: &str, message: &str, args: &[(&str, &str)]) -> String {
+        let args = args
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        format(locale, message, &args).expect("format")
+    }
+
+    const MESSAGES: &str =
+        "You have {count, plural, =0 {no new messages} one {# new message} other {# new messages}}";
+
+    #[test]
+    fn interpolates_and_keeps_unknown_placeholders() {
+        assert_eq!(
+            fmt("en", "Hi {name}, see {missing}", &[("name", "Ada")]),
+            "Hi Ada, see {missing}"
+        );
+        assert_eq!(fmt("en", "{ n , number } pts", &[("n", "5")]), "5 pts");
+        assert_eq!(
+            fmt("en", "don't '{'quoted'}' ''x''", &[]),
+            "don't {quoted} 'x'"
+        );
+    }
+
+    #[test]
+    fn plural_uses_locale_rules_exact_matches_and_offset() {
+        assert_eq!(
+            fmt("en", MESSAGES, &[("count", "0")]),
+            "You have no new messages"
+        );
+        assert_eq!(
+            fmt("en", MESSAGES, &[("count", "1")]),
+            "You have 1 new message"
+        );
+        assert_eq!(
+            fmt("en", MESSAGES, &[("count", "1.0")]),
+            "You have 1.0 new messages"
+        );
+        let ru = "{n, plural, one {# файл} few {# файла} many {# файлов} other {# файла}}";
+        assert_eq!(fmt("ru", ru, &[("n", "21")]), "21 файл");
+        assert_eq!(fmt("ru", ru, &[("n", "3")]), "3 файла");
+        assert_eq!(fmt("ru", ru, &[("n", "5")]), "5 файлов");
+        let guests = "{n, plural, offset:1 =0 {nobody} =1 {{host}} one {{host} and # other} other {{host} and # others}}";
+        assert_eq!(fmt("en", guests, &[("n", "1"), ("host", "Ada")]), "Ada");
+        assert_eq!(
+            fmt("en", guests, &[("n", "2"), ("host", "Ada")]),
+            "Ada and 1 other"
+        );
+        assert_eq!(
+            fmt("en", guests, &[("n", "4"), ("host", "Ada")]),
+            "Ada and 3 others"
+        );
+        assert_eq!(fmt("en", MESSAGES, &[]), "You have # new messages");
+    }
+
+    #[test]
+    fn selectordinal_and_nested_select() {
+        let place = "{n, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}";
+        assert_eq!(fmt("en", place, &[("n", "1")]), "1st");
+        assert_eq!(fmt("en", place, &[("n", "12")]), "12th");
+        assert_eq!(fmt("en", place, &[("n", "23")]), "23rd");
+        let invite = "{gender, select, female {{n, plural, one {she invites # guest} other {she invites # guests}}} other {they invite {n}}}";
+        assert_eq!(
+            fmt("en", invite, &[("gender", "female"), ("n", "2")]),
+            "she invites 2 guests"
+        );
+        assert_eq!(fmt("en", invite, &[("n", "2")]), "they invite 2");
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        for (message, problem) in [
+            ("{count, plural, one {#}}", "missing `other` branch"),
+            ("{count, plural, one {#} other {#}", "unclosed argument"),
+            ("{}", "expected an argument name"),
+            ("oops}", "unmatched `}`"),
+            (
+                "{n, plural, =x {a} other {b}}",
+                "expected a number after `=`",
+            ),
+            ("{{name}}", "expected an argument name"),
+        ] {
+            assert_eq!(validate(message).expect_err(message).problem, problem);
+        }
+    }
+}
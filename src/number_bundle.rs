@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use greentic_types::cbor::canonical;
+use serde::{Deserialize, Serialize};
+
+// Locale number formats plus currency data, as read from
+// `assets/number_formats.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumberBundle {
+    pub locales: BTreeMap<String, NumberFormat>,
+    pub currencies: BTreeMap<String, Currency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumberFormat {
+    pub decimal: String,
+    pub group: String,
+    // Primary group size, then the size repeated for higher groups
+    // (`[3, 2]` gives 12,34,567).
+    #[serde(default = "default_grouping")]
+    pub grouping: Vec<u8>,
+    // Integers need `primary + min_grouping` digits before the first
+    // separator is inserted (`2` keeps "1234" but gives "12 345").
+    #[serde(default = "default_min_grouping")]
+    pub min_grouping: u8,
+    // `#` is the number, `¤` the currency symbol.
+    pub currency: String,
+    // `#` is the number, `%` the percent sign.
+    pub percent: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Currency {
+    pub symbol: String,
+    // Minor unit digits used when the template does not pass `decimals`.
+    #[serde(default = "default_digits")]
+    pub digits: u8,
+}
+
+fn default_grouping() -> Vec<u8> {
+    vec![3]
+}
+
+fn default_min_grouping() -> u8 {
+    1
+}
+
+fn default_digits() -> u8 {
+    2
+}
+
+// Reads the number format table; a missing file yields an empty bundle so
+// helpers fall back to plain formatting.
+pub fn load_number_formats(path: &Path) -> Result<NumberBundle, String> {
+    if !path.exists() {
+        return Ok(NumberBundle::default());
+    }
+    let raw = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&raw).map_err(|err| err.to_string())
+}
+
+// Produces canonical CBOR bytes for reproducible build embedding.
+pub fn pack_number_formats_to_cbor(bundle: &NumberBundle) -> Result<Vec<u8>, String> {
+    canonical::to_canonical_cbor_allow_floats(bundle).map_err(|err| err.to_string())
+}
+
+#[allow(dead_code)]
+// Runtime decode helper used by src/helpers/number.rs.
+pub fn unpack_number_formats_from_cbor(bytes: &[u8]) -> Result<NumberBundle, String> {
+    canonical::from_cbor(bytes).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_roundtrip_keeps_defaults() {
+        let bundle: NumberBundle = serde_json::from_str(
+            r##"{
+                "locales": { "en": { "decimal": ".", "group": ",", "currency": "¤#", "percent": "#%" } },
+                "currencies": { "USD": { "symbol": "$" } }
+            }"##,
+        )
+        .expect("parse bundle");
+
+        let cbor = pack_number_formats_to_cbor(&bundle).expect("pack bundle");
+        let decoded = unpack_number_formats_from_cbor(&cbor).expect("decode bundle");
+
+        assert_eq!(decoded, bundle);
+        assert_eq!(decoded.locales["en"].grouping, vec![3]);
+        assert_eq!(decoded.currencies["USD"].digits, 2);
+    }
+}
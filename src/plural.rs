@@ -0,0 +1,448 @@
+// CLDR plural rules (cardinal and ordinal) for the locales we ship.
+// Rules follow CLDR 44 `plurals.xml` / `ordinals.xml`; compact exponent
+// operands (`e`, `c`) are always 0 since we never format in compact notation.
+// Languages without an entry (and CLDR root) only have `other`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl Category {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::Zero => "zero",
+            Category::One => "one",
+            Category::Two => "two",
+            Category::Few => "few",
+            Category::Many => "many",
+            Category::Other => "other",
+        }
+    }
+}
+
+// CLDR operands of a decimal number as written: "1.50" has i=1, v=2, f=50,
+// t=5. `n` is only kept when the value is integral, which is all the
+// `n = ...` / `n % m = ...` conditions can match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operands {
+    n: Option<u64>,
+    i: u64,
+    v: usize,
+    f: u64,
+    t: u64,
+}
+
+impl Operands {
+    // Accepts plain decimals ("-3", "1.50"); anything else is read as a float
+    // and uses its shortest representation. Returns `None` for non-numbers.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        Self::decimal(text).or_else(|| {
+            let value = text.parse::<f64>().ok().filter(|value| value.is_finite())?;
+            Self::decimal(&value.to_string())
+        })
+    }
+
+    fn decimal(text: &str) -> Option<Self> {
+        let unsigned = text.strip_prefix('-').unwrap_or(text);
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if integer.is_empty()
+            || !integer.bytes().all(|byte| byte.is_ascii_digit())
+            || !fraction.bytes().all(|byte| byte.is_ascii_digit())
+            || fraction.len() > 18
+        {
+            return None;
+        }
+        let i = integer.parse::<u64>().ok()?;
+        let f = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<u64>().ok()?
+        };
+        let trimmed = fraction.trim_end_matches('0');
+        let t = if trimmed.is_empty() {
+            0
+        } else {
+            trimmed.parse::<u64>().ok()?
+        };
+        Some(Self {
+            n: (f == 0).then_some(i),
+            i,
+            v: fraction.len(),
+            f,
+            t,
+        })
+    }
+
+    fn n_is(&self, values: &[u64]) -> bool {
+        self.n.is_some_and(|n| values.contains(&n))
+    }
+
+    fn n_in(&self, from: u64, to: u64) -> bool {
+        self.n.is_some_and(|n| (from..=to).contains(&n))
+    }
+
+    fn n_mod(&self, modulus: u64) -> Option<u64> {
+        self.n.map(|n| n % modulus)
+    }
+
+    // `e = 0 and i != 0 and i % 1000000 = 0 and v = 0`
+    fn millions(&self) -> bool {
+        self.i != 0 && self.i.is_multiple_of(1_000_000) && self.v == 0
+    }
+}
+
+fn language(locale: &str) -> String {
+    let normalized = locale.trim().replace('_', "-").to_ascii_lowercase();
+    match normalized.as_str() {
+        // European Portuguese keeps the English-style `one`.
+        "pt-pt" => normalized,
+        _ => normalized.split('-').next().unwrap_or_default().to_string(),
+    }
+}
+
+fn within(value: u64, from: u64, to: u64) -> bool {
+    (from..=to).contains(&value)
+}
+
+pub fn cardinal(locale: &str, ops: &Operands) -> Category {
+    use Category::*;
+    let i10 = ops.i % 10;
+    let i100 = ops.i % 100;
+    let f10 = ops.f % 10;
+    let f100 = ops.f % 100;
+    let integer = ops.v == 0;
+    let language = language(locale);
+    match language.as_str() {
+        "en" | "de" | "nl" | "sv" | "et" | "fi" | "ur" => {
+            if ops.i == 1 && integer {
+                One
+            } else {
+                Other
+            }
+        }
+        "it" | "es" | "pt-pt" => {
+            let one = if language == "es" {
+                ops.n_is(&[1])
+            } else {
+                ops.i == 1 && integer
+            };
+            if one {
+                One
+            } else if ops.millions() {
+                Many
+            } else {
+                Other
+            }
+        }
+        "fr" | "pt" => {
+            if ops.i <= 1 {
+                One
+            } else if ops.millions() {
+                Many
+            } else {
+                Other
+            }
+        }
+        "bg" | "el" | "hu" | "ml" | "mr" | "ne" | "no" | "ta" | "te" | "tr" => {
+            if ops.n_is(&[1]) {
+                One
+            } else {
+                Other
+            }
+        }
+        "da" => {
+            if ops.n_is(&[1]) || (ops.t != 0 && ops.i <= 1) {
+                One
+            } else {
+                Other
+            }
+        }
+        "hi" | "bn" | "gu" | "kn" | "fa" => {
+            if ops.i == 0 || ops.n_is(&[1]) {
+                One
+            } else {
+                Other
+            }
+        }
+        "pa" => {
+            if ops.n_in(0, 1) {
+                One
+            } else {
+                Other
+            }
+        }
+        "si" => {
+            if ops.n_is(&[0, 1]) || (ops.i == 0 && ops.f == 1) {
+                One
+            } else {
+                Other
+            }
+        }
+        "tl" => {
+            // `i = 1,2,3` is already covered by the `i % 10` condition.
+            let last = if integer { i10 } else { f10 };
+            if [4, 6, 9].contains(&last) {
+                Other
+            } else {
+                One
+            }
+        }
+        "ar" => match ops.n_mod(100) {
+            _ if ops.n_is(&[0]) => Zero,
+            _ if ops.n_is(&[1]) => One,
+            _ if ops.n_is(&[2]) => Two,
+            Some(rem) if within(rem, 3, 10) => Few,
+            Some(rem) if within(rem, 11, 99) => Many,
+            _ => Other,
+        },
+        "ru" | "uk" => {
+            if !integer {
+                Other
+            } else if i10 == 1 && i100 != 11 {
+                One
+            } else if within(i10, 2, 4) && !within(i100, 12, 14) {
+                Few
+            } else {
+                Many
+            }
+        }
+        "sr" | "hr" => {
+            if (integer && i10 == 1 && i100 != 11) || (f10 == 1 && f100 != 11) {
+                One
+            } else if (integer && within(i10, 2, 4) && !within(i100, 12, 14))
+                || (within(f10, 2, 4) && !within(f100, 12, 14))
+            {
+                Few
+            } else {
+                Other
+            }
+        }
+        "pl" => {
+            if !integer {
+                Other
+            } else if ops.i == 1 {
+                One
+            } else if within(i10, 2, 4) && !within(i100, 12, 14) {
+                Few
+            } else {
+                Many
+            }
+        }
+        "cs" | "sk" => {
+            if !integer {
+                Many
+            } else if ops.i == 1 {
+                One
+            } else if within(ops.i, 2, 4) {
+                Few
+            } else {
+                Other
+            }
+        }
+        "lt" => {
+            let n10 = ops.n_mod(10);
+            let teen = ops.n_mod(100).is_some_and(|rem| within(rem, 11, 19));
+            if n10 == Some(1) && !teen {
+                One
+            } else if n10.is_some_and(|rem| within(rem, 2, 9)) && !teen {
+                Few
+            } else if ops.f != 0 {
+                Many
+            } else {
+                Other
+            }
+        }
+        "lv" => {
+            let n10 = ops.n_mod(10);
+            let n100 = ops.n_mod(100);
+            if n10 == Some(0)
+                || n100.is_some_and(|rem| within(rem, 11, 19))
+                || (ops.v == 2 && within(f100, 11, 19))
+            {
+                Zero
+            } else if (n10 == Some(1) && n100 != Some(11))
+                || (f10 == 1 && (ops.v != 2 || f100 != 11))
+            {
+                One
+            } else {
+                Other
+            }
+        }
+        "ro" => {
+            let n100 = ops.n_mod(100);
+            if ops.i == 1 && integer {
+                One
+            } else if !integer || ops.n_is(&[0]) || n100.is_some_and(|rem| within(rem, 1, 19)) {
+                Few
+            } else {
+                Other
+            }
+        }
+        _ => Other,
+    }
+}
+
+pub fn ordinal(locale: &str, ops: &Operands) -> Category {
+    use Category::*;
+    let n10 = ops.n_mod(10);
+    let n100 = ops.n_mod(100);
+    match language(locale).as_str() {
+        "en" => match (n10, n100) {
+            (Some(1), Some(rem)) if rem != 11 => One,
+            (Some(2), Some(rem)) if rem != 12 => Two,
+            (Some(3), Some(rem)) if rem != 13 => Few,
+            _ => Other,
+        },
+        "fr" | "ms" | "vi" | "tl" | "ro" | "lo" => {
+            if ops.n_is(&[1]) {
+                One
+            } else {
+                Other
+            }
+        }
+        "hu" => {
+            if ops.n_is(&[1, 5]) {
+                One
+            } else {
+                Other
+            }
+        }
+        "it" => {
+            if ops.n_is(&[11, 8, 80, 800]) {
+                Many
+            } else {
+                Other
+            }
+        }
+        "sv" => match (n10, n100) {
+            (Some(1 | 2), Some(rem)) if rem != 11 && rem != 12 => One,
+            _ => Other,
+        },
+        "hi" | "gu" => {
+            if ops.n_is(&[1]) {
+                One
+            } else if ops.n_is(&[2, 3]) {
+                Two
+            } else if ops.n_is(&[4]) {
+                Few
+            } else if ops.n_is(&[6]) {
+                Many
+            } else {
+                Other
+            }
+        }
+        "bn" => {
+            if ops.n_is(&[1, 5, 7, 8, 9, 10]) {
+                One
+            } else if ops.n_is(&[2, 3]) {
+                Two
+            } else if ops.n_is(&[4]) {
+                Few
+            } else if ops.n_is(&[6]) {
+                Many
+            } else {
+                Other
+            }
+        }
+        "mr" => {
+            if ops.n_is(&[1]) {
+                One
+            } else if ops.n_is(&[2, 3]) {
+                Two
+            } else if ops.n_is(&[4]) {
+                Few
+            } else {
+                Other
+            }
+        }
+        "ne" => {
+            if ops.n_in(1, 4) {
+                One
+            } else {
+                Other
+            }
+        }
+        "uk" => match (n10, n100) {
+            (Some(3), Some(rem)) if rem != 13 => Few,
+            _ => Other,
+        },
+        _ => Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(locale: &str, number: &str) -> &'static str {
+        cardinal(locale, &Operands::parse(number).expect("number")).as_str()
+    }
+
+    fn ord(locale: &str, number: &str) -> &'static str {
+        ordinal(locale, &Operands::parse(number).expect("number")).as_str()
+    }
+
+    #[test]
+    fn operands_follow_visible_digits() {
+        let ops = Operands::parse("-1.50").expect("number");
+        assert_eq!((ops.n, ops.i, ops.v, ops.f, ops.t), (None, 1, 2, 50, 5));
+        let ops = Operands::parse("2.0").expect("number");
+        assert_eq!((ops.n, ops.v), (Some(2), 1));
+        assert_eq!(Operands::parse("1e3"), Operands::parse("1000"));
+        assert!(Operands::parse("many").is_none());
+    }
+
+    #[test]
+    fn cardinal_rules() {
+        assert_eq!(card("en", "1"), "one");
+        assert_eq!(card("en", "1.0"), "other");
+        assert_eq!(card("fr-FR", "0"), "one");
+        assert_eq!(card("fr", "1.5"), "one");
+        assert_eq!(card("fr", "1000000"), "many");
+        assert_eq!(card("pt", "0"), "one");
+        assert_eq!(card("pt-PT", "0"), "other");
+        assert_eq!(card("ru", "21"), "one");
+        assert_eq!(card("ru", "22"), "few");
+        assert_eq!(card("ru", "11"), "many");
+        assert_eq!(card("uk", "1.5"), "other");
+        assert_eq!(card("pl", "1"), "one");
+        assert_eq!(card("pl", "24"), "few");
+        assert_eq!(card("pl", "25"), "many");
+        assert_eq!(card("cs", "3"), "few");
+        assert_eq!(card("cs", "0.5"), "many");
+        assert_eq!(card("ar-EG", "0"), "zero");
+        assert_eq!(card("ar", "2"), "two");
+        assert_eq!(card("ar", "103"), "few");
+        assert_eq!(card("ar", "111"), "many");
+        assert_eq!(card("ar", "100"), "other");
+        assert_eq!(card("lt", "0.1"), "many");
+        assert_eq!(card("lv", "10"), "zero");
+        assert_eq!(card("lv", "21"), "one");
+        assert_eq!(card("ro", "19"), "few");
+        assert_eq!(card("ro", "20"), "other");
+        assert_eq!(card("hi", "0"), "one");
+        assert_eq!(card("sr", "0.1"), "one");
+        assert_eq!(card("ja", "1"), "other");
+        assert_eq!(card("nah", "1"), "other");
+    }
+
+    #[test]
+    fn ordinal_rules() {
+        assert_eq!(ord("en", "1"), "one");
+        assert_eq!(ord("en", "22"), "two");
+        assert_eq!(ord("en", "113"), "other");
+        assert_eq!(ord("en-GB", "103"), "few");
+        assert_eq!(ord("fr", "1"), "one");
+        assert_eq!(ord("sv", "12"), "other");
+        assert_eq!(ord("it", "8"), "many");
+        assert_eq!(ord("bn", "6"), "many");
+        assert_eq!(ord("de", "1"), "other");
+    }
+}
@@ -0,0 +1,540 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use handlebars::template::TemplateElement;
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderErrorReason,
+    Renderable, StringOutput, Template,
+};
+use serde_json::Value as JsonValue;
+
+use crate::cache;
+use crate::diagnostics::{self, Diagnostic};
+use crate::helpers;
+
+// Handlebars' own helpers that the component does not override; together
+// with `helpers::names()` these are the candidates for did-you-mean
+// suggestions.
+const BUILTIN_HELPERS: &[&str] = &[
+    "if", "unless", "each", "with", "lookup", "raw", "log", "len",
+];
+
+// Deepest `{{> partial}}` nesting allowed. Partials may include themselves
+// (e.g. to walk a tree); the limit turns runaway recursion into an error
+// instead of a stack overflow.
+pub const MAX_PARTIAL_DEPTH: usize = 16;
+
+// Block helper every compiled partial is wrapped in to count nesting.
+const PARTIAL_GUARD: &str = "__partial";
+
+// Failure modes surfaced by the renderer. Each carries located diagnostics so
+// callers can map them onto the component error envelope without depending
+// on handlebars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError {
+    Syntax(Box<Diagnostic>),
+    Render(Box<Diagnostic>),
+    // Strict mode: variables the template referenced but the context lacks.
+    MissingScope(Vec<Diagnostic>),
+}
+
+impl RenderError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Syntax(_) | Self::Render(_) => "template_render",
+            Self::MissingScope(_) => "missing_scope",
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            Self::Syntax(diagnostic) | Self::Render(diagnostic) => {
+                std::slice::from_ref(diagnostic.as_ref())
+            }
+            Self::MissingScope(diagnostics) => diagnostics,
+        }
+    }
+
+    pub fn missing_paths(&self) -> Vec<&str> {
+        match self {
+            Self::MissingScope(diagnostics) => diagnostics
+                .iter()
+                .filter_map(|diagnostic| diagnostic.token.as_deref())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(diagnostic) => write!(f, "template syntax error: {}", diagnostic.detail),
+            Self::Render(diagnostic) => {
+                write!(f, "template render failed: {}", diagnostic.detail)
+            }
+            Self::MissingScope(_) => {
+                write!(
+                    f,
+                    "template references missing variables: {}",
+                    self.missing_paths().join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    // Fail on unresolved variables instead of rendering them as empty.
+    pub strict: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self { strict: true }
+    }
+}
+
+// Rendered text plus the variables that resolved to nothing (lenient mode).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rendered {
+    pub text: String,
+    pub missing: Vec<String>,
+}
+
+// Parses a template source into its compiled form.
+pub fn compile(source: &str) -> Result<Template, RenderError> {
+    Template::compile(source)
+        .map_err(|err| RenderError::Syntax(Box::new(diagnostics::syntax(source, &err))))
+}
+
+// Renders `source` against a JSON context using the component registry.
+//
+// Strict mode uses Handlebars' own strict lookups, so `#each`/`#with` over a
+// missing value fails too. On failure the template is re-rendered leniently
+// to name every missing path, not just the first one Handlebars hit.
+pub fn render_template(
+    source: &str,
+    context: &JsonValue,
+    options: RenderOptions,
+) -> Result<Rendered, RenderError> {
+    render_template_hashed(source, &cache::template_hash(source), context, options)
+}
+
+// `render_template` for sources whose hash is already known, such as configs
+// validated by apply-answers, so hot paths skip hashing the source again.
+pub fn render_template_hashed(
+    source: &str,
+    hash: &str,
+    context: &JsonValue,
+    options: RenderOptions,
+) -> Result<Rendered, RenderError> {
+    render_with_partials(source, hash, context, options, &BTreeMap::new())
+}
+
+// `render_template_hashed` with named partials and layouts
+// (`TemplatesConfig::partial_sources`) available to `{{> name}}`.
+pub fn render_with_partials(
+    source: &str,
+    hash: &str,
+    context: &JsonValue,
+    options: RenderOptions,
+    partials: &BTreeMap<&str, &str>,
+) -> Result<Rendered, RenderError> {
+    let template = cache::compiled_template_keyed(hash, source, compile)?;
+    if !options.strict {
+        return render_lenient(source, &template, partials, context);
+    }
+
+    let registry = registry(partials, true)?;
+    match render_compiled(&registry, &template, context) {
+        Ok(text) => Ok(Rendered {
+            text,
+            missing: Vec::new(),
+        }),
+        Err(err) => match err.reason() {
+            RenderErrorReason::MissingVariable(path) => {
+                let mut missing = render_lenient(source, &template, partials, context)
+                    .map(|rendered| rendered.missing)
+                    .unwrap_or_default();
+                if let Some(path) = path
+                    && !missing.contains(path)
+                {
+                    missing.insert(0, path.clone());
+                }
+                Err(RenderError::MissingScope(
+                    missing
+                        .iter()
+                        .map(|path| diagnostics::missing_scope(source, path))
+                        .collect(),
+                ))
+            }
+            _ => Err(render_failure(source, &err)),
+        },
+    }
+}
+
+// Compiled form of a partial, wrapped in the depth guard. Cached next to
+// templates under its own key, since the wrapped form differs from the same
+// source compiled as a template.
+pub fn compiled_partial(source: &str) -> Result<Arc<Template>, RenderError> {
+    let key = format!("partial:{}", cache::template_hash(source));
+    cache::compiled_template_keyed(&key, source, |source| {
+        let partial = compile(source)?;
+        let mut guard =
+            Template::compile(&format!("{{{{#{PARTIAL_GUARD}}}}}{{{{/{PARTIAL_GUARD}}}}}"))
+                .expect("partial guard template is valid");
+        if let Some(TemplateElement::HelperBlock(block)) = guard.elements.first_mut() {
+            block.template = Some(partial);
+        }
+        Ok(guard)
+    })
+}
+
+fn render_lenient(
+    source: &str,
+    template: &Template,
+    partials: &BTreeMap<&str, &str>,
+    context: &JsonValue,
+) -> Result<Rendered, RenderError> {
+    let registry = registry(partials, false)?;
+    take_missing();
+    let text = render_compiled(&registry, template, context)
+        .map_err(|err| render_failure(source, &err))?;
+    Ok(Rendered {
+        text,
+        missing: take_missing(),
+    })
+}
+
+fn render_failure(source: &str, err: &handlebars::RenderError) -> RenderError {
+    let known: Vec<&str> = BUILTIN_HELPERS
+        .iter()
+        .copied()
+        .chain(helpers::names())
+        .collect();
+    RenderError::Render(Box::new(diagnostics::render_failure(source, err, &known)))
+}
+
+// Renders an already compiled template without registering (and cloning) it.
+// Each render starts a fresh random stream, so seeded output is replayable.
+fn render_compiled(
+    registry: &Handlebars<'static>,
+    template: &Template,
+    context: &JsonValue,
+) -> Result<String, handlebars::RenderError> {
+    let context = Context::wraps(context)?;
+    helpers::random::reset();
+    let mut render_context = RenderContext::new(template.name.as_ref());
+    let mut output = StringOutput::new();
+    template.render(registry, &context, &mut render_context, &mut output)?;
+    Ok(output.into_string()?)
+}
+
+// Evaluates a route condition against the node's `partials`, so it shares
+// the node's lenient registry. Bare expressions go through `#if` so they get
+// Handlebars truthiness; full templates are truthy when they render to
+// anything other than blank, `false` or `0`. Conditions are always lenient:
+// a missing field simply does not match.
+pub fn evaluate_condition(
+    condition: &str,
+    context: &JsonValue,
+    partials: &BTreeMap<&str, &str>,
+) -> Result<bool, RenderError> {
+    let lenient = RenderOptions { strict: false };
+    let render = |source: &str| {
+        let hash = cache::template_hash(source);
+        render_with_partials(source, &hash, context, lenient, partials)
+    };
+    if condition.contains("{{") {
+        let rendered = render(condition)?;
+        let rendered = rendered.text.trim();
+        return Ok(!matches!(rendered, "" | "false" | "0"));
+    }
+    let probe = format!("{{{{#if {condition}}}}}1{{{{/if}}}}");
+    Ok(!render(&probe)?.text.is_empty())
+}
+
+// Registry with the component defaults and `partials`, prepared once per mode
+// and partial set. Escaping stays enabled; authors opt out with triple-stash
+// `{{{ }}}` as documented in the README.
+fn registry(
+    partials: &BTreeMap<&str, &str>,
+    strict: bool,
+) -> Result<Arc<Handlebars<'static>>, RenderError> {
+    let mut content = String::new();
+    for (name, source) in partials {
+        content.extend([*name, "\0", *source, "\0"]);
+    }
+    let key = format!("registry:{strict}:{}", cache::template_hash(&content));
+    cache::prepared_registry(&key, || {
+        let mut registry = helper_registry().clone();
+        registry.set_strict_mode(strict);
+        for (name, source) in partials {
+            registry.register_template(name, Template::clone(&*compiled_partial(source)?));
+        }
+        Ok(registry)
+    })
+}
+
+// Built-in helpers and hooks, registered once per process. Cloning it only
+// copies `Arc`s, so prepared registries start from a copy.
+fn helper_registry() -> &'static Handlebars<'static> {
+    static HELPERS: OnceLock<Handlebars<'static>> = OnceLock::new();
+    HELPERS.get_or_init(|| {
+        let mut registry = Handlebars::new();
+        helpers::register(&mut registry);
+        registry.register_helper("helperMissing", Box::new(MissingTracker));
+        registry.register_helper(PARTIAL_GUARD, Box::new(PartialGuard));
+        registry
+    })
+}
+
+thread_local! {
+    // Partial nesting of the render in progress. Registries are shared, so
+    // the count lives with the rendering thread rather than in the helper.
+    static PARTIAL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+// Counts partial nesting for one render and fails past `MAX_PARTIAL_DEPTH`.
+struct PartialGuard;
+
+impl HelperDef for PartialGuard {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        registry: &'reg Handlebars<'reg>,
+        context: &'rc Context,
+        render_context: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let depth = PARTIAL_DEPTH.get() + 1;
+        PARTIAL_DEPTH.set(depth);
+        let result = match h.template() {
+            _ if depth > MAX_PARTIAL_DEPTH => Err(RenderErrorReason::Other(format!(
+                "partials are nested more than {MAX_PARTIAL_DEPTH} levels deep"
+            ))
+            .into()),
+            Some(partial) => partial.render(registry, context, render_context, out),
+            None => Ok(()),
+        };
+        PARTIAL_DEPTH.set(depth - 1);
+        result
+    }
+}
+
+thread_local! {
+    // Paths the current lenient render could not resolve, in first-use order.
+    static MISSING: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+// Records an unresolved path for the lenient render in progress; helpers
+// call this for missing arguments.
+pub fn record_missing(path: &str) {
+    MISSING.with(|missing| {
+        let mut missing = missing.borrow_mut();
+        if !missing.iter().any(|known| known == path) {
+            missing.push(path.to_string());
+        }
+    });
+}
+
+fn take_missing() -> Vec<String> {
+    MISSING.with(|missing| std::mem::take(&mut *missing.borrow_mut()))
+}
+
+// `helperMissing` hook. Handlebars calls it for `{{name}}` expressions whose
+// value is missing (lenient mode) and for calls to unregistered helpers; the
+// former are recorded, the latter stay errors.
+struct MissingTracker;
+
+impl HelperDef for MissingTracker {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        _: &mut dyn Output,
+    ) -> HelperResult {
+        if !h.params().is_empty() || !h.hash().is_empty() {
+            return Err(RenderErrorReason::HelperNotFound(h.name().to_string()).into());
+        }
+        record_missing(h.name());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_payload_fields() {
+        let rendered = render_template(
+            "Hello {{payload.name}}",
+            &json!({ "payload": { "name": "Greentic" } }),
+            RenderOptions::default(),
+        )
+        .expect("render");
+        assert_eq!(rendered.text, "Hello Greentic");
+    }
+
+    #[test]
+    fn strict_mode_names_every_missing_path() {
+        let err = render_template(
+            "{{payload.first}} {{payload.name}} {{msg.locale}}",
+            &json!({ "payload": { "name": "Ada" }, "msg": {} }),
+            RenderOptions { strict: true },
+        )
+        .expect_err("missing scope");
+        assert_eq!(err.missing_paths(), ["payload.first", "msg.locale"]);
+        assert_eq!(err.kind(), "missing_scope");
+        let located = &err.diagnostics()[1];
+        assert_eq!((located.line, located.column), (Some(1), Some(38)));
+    }
+
+    #[test]
+    fn strict_mode_rejects_iterating_missing_values() {
+        let err = render_template(
+            "{{#each payload.items}}{{this}}{{/each}}",
+            &json!({ "payload": {} }),
+            RenderOptions { strict: true },
+        )
+        .expect_err("missing scope");
+        assert!(matches!(err, RenderError::MissingScope(_)));
+        assert_eq!(err.missing_paths(), ["payload.items"]);
+    }
+
+    #[test]
+    fn lenient_mode_renders_and_reports_missing_paths() {
+        let rendered = render_template(
+            "Hi {{payload.name}}{{payload.suffix}}",
+            &json!({ "payload": { "name": "Ada" } }),
+            RenderOptions { strict: false },
+        )
+        .expect("render");
+        assert_eq!(rendered.text, "Hi Ada");
+        assert_eq!(rendered.missing, ["payload.suffix"]);
+    }
+
+    #[test]
+    fn missing_helper_arguments_follow_the_mode() {
+        let source = "{{upper payload.nme}} {{t \"x\" name=payload.who}} \
+            {{get payload.opt \"a\" \"-\"}}";
+        let context = json!({ "payload": {}, "locale": "en" });
+        let err = render_template(source, &context, RenderOptions { strict: true })
+            .expect_err("missing scope");
+        assert_eq!(err.missing_paths(), ["payload.nme", "payload.who"]);
+
+        let rendered =
+            render_template(source, &context, RenderOptions { strict: false }).expect("render");
+        assert_eq!(rendered.text, " x -");
+        assert_eq!(rendered.missing, ["payload.nme", "payload.who"]);
+    }
+
+    #[test]
+    fn unknown_helpers_still_fail() {
+        let err = render_template(
+            "{{shout payload.name}}",
+            &json!({ "payload": { "name": "Ada" } }),
+            RenderOptions { strict: false },
+        )
+        .expect_err("unknown helper");
+        let RenderError::Render(diagnostic) = err else {
+            panic!("expected render error, got {err:?}");
+        };
+        assert_eq!(diagnostic.code, diagnostics::CODE_UNKNOWN_HELPER);
+        assert_eq!(diagnostic.token.as_deref(), Some("shout"));
+    }
+
+    #[test]
+    fn unknown_helpers_suggest_registered_names() {
+        let err = render_template(
+            "Hi\n{{#unles payload.vip}}x{{/unles}}",
+            &json!({ "payload": { "vip": false } }),
+            RenderOptions::default(),
+        )
+        .expect_err("unknown helper");
+        let diagnostic = &err.diagnostics()[0];
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(2), Some(4)));
+        let fix = diagnostic.fix.as_ref().expect("suggestion");
+        assert_eq!(fix.args["suggestion"], "unless");
+    }
+
+    #[test]
+    fn partials_recurse_up_to_the_depth_limit() {
+        let partials = BTreeMap::from([(
+            "node",
+            "{{name}}{{#if children}}({{#each children}}{{> node}}{{/each}}){{/if}}",
+        )]);
+        let render = |context: &JsonValue| {
+            let source = "{{> node tree}}";
+            let hash = cache::template_hash(source);
+            render_with_partials(source, &hash, context, RenderOptions::default(), &partials)
+        };
+        let tree = json!({ "tree": { "name": "a", "children": [
+            { "name": "b", "children": [{ "name": "c" }] },
+            { "name": "d" }
+        ] } });
+        assert_eq!(render(&tree).expect("render").text, "a(b(c)d)");
+
+        let mut deep = json!({ "name": "leaf" });
+        for _ in 0..MAX_PARTIAL_DEPTH {
+            deep = json!({ "name": "n", "children": [deep] });
+        }
+        let RenderError::Render(diagnostic) = render(&json!({ "tree": deep })).expect_err("depth")
+        else {
+            panic!("expected render error");
+        };
+        assert_eq!(
+            diagnostic.detail,
+            "partials are nested more than 16 levels deep"
+        );
+    }
+
+    #[test]
+    fn registries_are_prepared_once_per_mode_and_partial_set() {
+        let partials = BTreeMap::from([("registry_test", "[{{> @partial-block}}]")]);
+        let strict = registry(&partials, true).expect("registry");
+        assert!(Arc::ptr_eq(
+            &strict,
+            &registry(&partials, true).expect("registry")
+        ));
+        assert!(strict.strict_mode());
+        assert!(strict.has_template("registry_test"));
+
+        let lenient = registry(&partials, false).expect("registry");
+        assert!(!Arc::ptr_eq(&strict, &lenient));
+        let other = BTreeMap::from([("registry_test", "({{> @partial-block}})")]);
+        assert!(!Arc::ptr_eq(
+            &strict,
+            &registry(&other, true).expect("registry")
+        ));
+    }
+
+    #[test]
+    fn evaluates_expression_and_template_conditions() {
+        let context = json!({ "payload": { "vip": true, "tier": "gold", "count": 0 } });
+        assert!(evaluate_condition("payload.vip", &context, &BTreeMap::new()).expect("eval"));
+        assert!(!evaluate_condition("payload.count", &context, &BTreeMap::new()).expect("eval"));
+        assert!(!evaluate_condition("payload.missing", &context, &BTreeMap::new()).expect("eval"));
+        assert!(evaluate_condition("{{payload.tier}}", &context, &BTreeMap::new()).expect("eval"));
+        assert!(
+            !evaluate_condition("{{payload.count}}", &context, &BTreeMap::new()).expect("eval")
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let err = render_template("Hello {{#if}}", &json!({}), RenderOptions::default())
+            .expect_err("syntax error");
+        assert!(matches!(err, RenderError::Syntax(_)));
+    }
+}
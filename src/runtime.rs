@@ -0,0 +1,648 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::{Value as JsonValue, json};
+
+use crate::cache;
+use crate::config::{self, TemplatesConfig};
+use crate::context::{build_context, caller_locale, tenant_locale};
+use crate::diagnostics::{self, Diagnostic};
+use crate::i18n;
+use crate::render::{self, RenderError, RenderOptions};
+
+pub const OP_TEXT: &str = "text";
+// Compatibility alias for flows generated before `text` was exposed.
+pub const OP_HANDLE_MESSAGE: &str = "handle_message";
+pub const SUPPORTED_OPERATIONS: &[&str] = &[OP_TEXT, OP_HANDLE_MESSAGE];
+
+// Errors raised while turning an invocation into rendered output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunError {
+    InvalidInput(String),
+    UnsupportedOperation(String),
+    Render(RenderError),
+}
+
+impl RunError {
+    // Message family; selects the `errors.<kind>` translation key.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidInput(_) => "invalid_input",
+            Self::UnsupportedOperation(_) => "unsupported_operation",
+            Self::Render(err) => err.kind(),
+        }
+    }
+
+    // Stable machine-readable code; finer grained than `kind`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Render(RenderError::Syntax(_)) => diagnostics::CODE_TEMPLATE_SYNTAX,
+            Self::Render(RenderError::Render(diagnostic)) => diagnostic.code,
+            _ => self.kind(),
+        }
+    }
+
+    pub fn i18n_key(&self) -> String {
+        format!("errors.{}", self.kind())
+    }
+
+    // Placeholder values interpolated into the translated message.
+    pub fn args(&self) -> BTreeMap<String, String> {
+        let args = match self {
+            Self::InvalidInput(reason) => vec![("reason", reason.clone())],
+            Self::UnsupportedOperation(operation) => vec![
+                ("operation", operation.clone()),
+                ("supported", SUPPORTED_OPERATIONS.join("`, `")),
+            ],
+            Self::Render(err @ RenderError::MissingScope(_)) => {
+                vec![("paths", err.missing_paths().join(", "))]
+            }
+            Self::Render(err) => vec![("reason", err.to_string())],
+        };
+        args.into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    }
+
+    pub fn details(&self) -> JsonValue {
+        match self {
+            Self::Render(err @ RenderError::MissingScope(_)) => {
+                json!({ "paths": err.missing_paths() })
+            }
+            _ => json!({}),
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            Self::Render(err) => err.diagnostics(),
+            _ => &[],
+        }
+    }
+
+    // Error payload shape documented in schemas/io/output.schema.json, with
+    // `message` and diagnostics resolved for the caller's locale. It always
+    // routes to the `error` port, whatever the node's `wrap` and `routes`.
+    pub fn to_output(&self, locale: &str) -> JsonValue {
+        let key = self.i18n_key();
+        let args = self.args();
+        let diagnostics: Vec<JsonValue> = self
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.to_json(locale))
+            .collect();
+        json!({
+            "error": {
+                "kind": self.kind(),
+                "code": self.code(),
+                "key": key,
+                "message": i18n::t_args(locale, &key, &args),
+                "args": args,
+                "details": self.details(),
+                "diagnostics": diagnostics,
+            },
+            "control": { "routing": config::ERROR_ROUTE },
+        })
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidInput(message) => write!(f, "invalid input: {message}"),
+            Self::UnsupportedOperation(operation) => write!(
+                f,
+                "operation `{operation}` is not supported; use `{}`",
+                SUPPORTED_OPERATIONS.join("`, `")
+            ),
+            Self::Render(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+impl From<RenderError> for RunError {
+    fn from(err: RenderError) -> Self {
+        Self::Render(err)
+    }
+}
+
+// Locates the node `templates` config block.
+// Flows emit `{ "templates": { ... } }` directly while the test harness and
+// pack smoke test wrap it as `{ "config": { "templates": { ... } } }`.
+pub fn templates_config(invocation: &JsonValue) -> Option<&JsonValue> {
+    invocation
+        .get("config")
+        .and_then(|config| config.get("templates"))
+        .or_else(|| invocation.get("templates"))
+        .filter(|templates| templates.is_object())
+}
+
+// Runs an invocation and folds failures into the localized error envelope.
+pub fn run_to_output(invocation: &JsonValue, state: &JsonValue) -> JsonValue {
+    run(invocation, state).unwrap_or_else(|err| err.to_output(&caller_locale(invocation)))
+}
+
+// The runtime export carries no operation argument, so hosts name it in the
+// envelope (`operation`, or the shorter `op`). Absent means `text`.
+pub fn operation(invocation: &JsonValue) -> &str {
+    invocation
+        .get("operation")
+        .or_else(|| invocation.get("op"))
+        .and_then(JsonValue::as_str)
+        .unwrap_or(OP_TEXT)
+}
+
+// Routes an invocation to the handler for its operation.
+pub fn run(invocation: &JsonValue, state: &JsonValue) -> Result<JsonValue, RunError> {
+    match operation(invocation) {
+        OP_TEXT => run_text(invocation, state),
+        OP_HANDLE_MESSAGE => run_handle_message(invocation, state),
+        other => Err(RunError::UnsupportedOperation(other.to_string())),
+    }
+}
+
+// Renders `templates.text`, or the `text_i18n` variant for the caller's
+// locale, for a single invocation. The result is stored at
+// `templates.output_path` next to `control.routing` (plus lenient-mode
+// `warnings` and the opt-in `debug` block), or emitted as a bare string when
+// `wrap` is false.
+pub fn run_text(invocation: &JsonValue, state: &JsonValue) -> Result<JsonValue, RunError> {
+    let templates = templates_config(invocation)
+        .ok_or_else(|| RunError::InvalidInput("`templates.text` is required".to_string()))?;
+    let config = TemplatesConfig::from_value(templates).map_err(RunError::InvalidInput)?;
+    let segments = config.output_segments().map_err(RunError::InvalidInput)?;
+    config.check_wrap().map_err(RunError::InvalidInput)?;
+
+    let context = build_context(invocation, state);
+    config
+        .check_seed(context.pointer("/msg/id"))
+        .map_err(RunError::InvalidInput)?;
+    let options = RenderOptions {
+        strict: config.strict,
+    };
+    let locale = caller_locale(invocation);
+    let locales: Vec<&str> = std::iter::once(locale.as_str())
+        .chain(tenant_locale(invocation))
+        .collect();
+    let (variant, text) = config.text_for(&locales);
+    let hash = match (variant, &config.template_hash) {
+        (None, Some(hash)) => hash.clone(),
+        _ => cache::template_hash(text),
+    };
+    let rendered =
+        render::render_with_partials(text, &hash, &context, options, &config.partial_sources())?;
+    if !config.wrap {
+        return Ok(JsonValue::String(rendered.text));
+    }
+    let route = select_route(&config, &context)?;
+    let mut output = nest_at_path(&segments, JsonValue::String(rendered.text));
+    if let JsonValue::Object(map) = &mut output {
+        map.insert("control".to_string(), json!({ "routing": route }));
+        if !rendered.missing.is_empty() {
+            map.insert("warnings".to_string(), missing_warnings(&rendered.missing));
+        }
+        if config.debug {
+            map.insert("debug".to_string(), debug_block(&hash, variant));
+        }
+    }
+    Ok(output)
+}
+
+// `variant` is the `text_i18n` locale rendered, `null` for `text`.
+fn debug_block(hash: &str, variant: Option<&str>) -> JsonValue {
+    json!({
+        "template_hash": hash,
+        "variant": variant,
+        "cache": cache::template_cache_stats(),
+    })
+}
+
+// Lenient-mode report of variables that rendered as empty.
+fn missing_warnings(paths: &[String]) -> JsonValue {
+    paths
+        .iter()
+        .map(|path| json!({ "kind": "missing_scope", "path": path }))
+        .collect()
+}
+
+// First `routes` entry whose condition holds, else `routing` (default `out`).
+pub fn select_route(config: &TemplatesConfig, context: &JsonValue) -> Result<String, RunError> {
+    let partials = config.partial_sources();
+    for route in &config.routes {
+        if render::evaluate_condition(&route.when, context, &partials)? {
+            return Ok(route.to.clone());
+        }
+    }
+    Ok(config.default_route().to_string())
+}
+
+// Wraps `value` in one object per path segment, innermost last.
+pub fn nest_at_path(segments: &[&str], value: JsonValue) -> JsonValue {
+    segments.iter().rev().fold(value, |inner, segment| {
+        let mut object = serde_json::Map::new();
+        object.insert((*segment).to_string(), inner);
+        JsonValue::Object(object)
+    })
+}
+
+// Legacy entry point: renders like `text` when a template is configured and
+// otherwise echoes the `{ input }` payload the way the scaffold used to.
+pub fn run_handle_message(
+    invocation: &JsonValue,
+    state: &JsonValue,
+) -> Result<JsonValue, RunError> {
+    if templates_config(invocation).is_some() {
+        return run_text(invocation, state);
+    }
+    let input = invocation
+        .get("input")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| {
+            RunError::InvalidInput("`input` or `templates.text` is required".to_string())
+        })?;
+    Ok(json!({ "message": crate::handle_message(OP_HANDLE_MESSAGE, input) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_wrapped_config_from_harness_shape() {
+        let invocation = json!({
+            "config": { "templates": { "text": "Hello {{payload.name}}" } },
+            "msg": { "id": "msg-1", "channel": "chat" },
+            "payload": { "name": "Greentic" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(
+            output,
+            json!({ "text": "Hello Greentic", "control": { "routing": "out" } })
+        );
+    }
+
+    #[test]
+    fn renders_flat_templates_config_with_msg_and_state() {
+        let invocation = json!({
+            "templates": { "text": "{{msg.channel}}:{{state.count}}" },
+            "msg": { "channel": "chat" }
+        });
+        let output = run(&invocation, &json!({ "count": 3 })).expect("run");
+        assert_eq!(output["text"], "chat:3");
+    }
+
+    #[test]
+    fn renders_default_template_with_bare_payload_identifiers() {
+        let invocation = json!({
+            "templates": { "text": "Hello {{name}} ({{tenant.tenant_id}}, try {{meta.attempt}})" },
+            "msg": { "id": "msg-1", "tenant": { "tenant_id": "acme", "attempt": 1 } },
+            "payload": { "name": "Greentic" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(output["text"], "Hello Greentic (acme, try 1)");
+    }
+
+    #[test]
+    fn translates_with_tenant_catalog_for_message_locale() {
+        let invocation = json!({
+            "templates": {
+                "text": "{{t \"welcome\" name=name}}",
+                "messages": {
+                    "en": { "welcome": "Welcome, {name}" },
+                    "nl": { "welcome": "Welkom, {name}" }
+                }
+            },
+            "msg": { "locale": "nl-NL" },
+            "payload": { "name": "Ada" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(output["text"], "Welkom, Ada");
+    }
+
+    #[test]
+    fn picks_text_variant_for_message_then_tenant_locale() {
+        let render = |msg: JsonValue| {
+            let invocation = json!({
+                "templates": {
+                    "text": "Hi {{name}}",
+                    "text_i18n": { "fr": "Bonjour {{name}}", "de": "Hallo {{name}}" },
+                    "debug": true
+                },
+                "msg": msg,
+                "payload": { "name": "Ada" }
+            });
+            run(&invocation, &JsonValue::Null).expect("run")
+        };
+        let output = render(json!({ "locale": "fr-CA", "tenant": { "i18n_id": "de" } }));
+        assert_eq!(output["text"], "Bonjour Ada");
+        assert_eq!(output["debug"]["variant"], "fr");
+        let output = render(json!({ "locale": "nl", "tenant": { "i18n_id": "de-AT" } }));
+        assert_eq!(output["text"], "Hallo Ada");
+        let output = render(json!({ "locale": "nl" }));
+        assert_eq!(output["text"], "Hi Ada");
+        assert_eq!(output["debug"]["variant"], JsonValue::Null);
+    }
+
+    #[test]
+    fn includes_config_partials_with_hash_arguments() {
+        let invocation = json!({
+            "templates": {
+                "text": "Thanks, {{payload.name}}.\n{{> signature team=\"Support\"}}",
+                "partials": {
+                    "signature": "-- {{team}}, {{> disclaimer}}",
+                    "disclaimer": "replies are monitored"
+                }
+            },
+            "payload": { "name": "Ada" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(
+            output["text"],
+            "Thanks, Ada.\n-- Support, replies are monitored"
+        );
+    }
+
+    #[test]
+    fn bare_payload_renders_compact_json() {
+        let invocation = json!({
+            "templates": { "text": "Debug: {{{payload}}}" },
+            "payload": { "items": [1, 2], "name": "Ada" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(output["text"], r#"Debug: {"items":[1,2],"name":"Ada"}"#);
+    }
+
+    #[test]
+    fn writes_rendered_text_at_nested_output_path() {
+        let invocation = json!({
+            "templates": { "text": "Hi {{payload.name}}", "output_path": "reply.body.text" },
+            "payload": { "name": "Ada" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(output["reply"], json!({ "body": { "text": "Hi Ada" } }));
+    }
+
+    #[test]
+    fn first_truthy_route_wins_and_default_applies_otherwise() {
+        let templates = json!({
+            "text": "Hi",
+            "routing": "standard",
+            "routes": [
+                { "when": "payload.blocked", "to": "blocked" },
+                { "when": "payload.vip", "to": "vip" },
+                { "when": "{{#if payload.vip}}yes{{/if}}", "to": "unreachable" }
+            ]
+        });
+        let vip = json!({ "templates": templates, "payload": { "vip": true } });
+        let output = run(&vip, &JsonValue::Null).expect("run");
+        assert_eq!(output["control"]["routing"], "vip");
+
+        let regular = json!({ "templates": templates, "payload": {} });
+        let output = run(&regular, &JsonValue::Null).expect("run");
+        assert_eq!(output["control"]["routing"], "standard");
+    }
+
+    #[test]
+    fn strict_mode_fails_with_missing_paths() {
+        let invocation = json!({
+            "templates": { "text": "Hi {{payload.name}} {{payload.title}}" },
+            "payload": {}
+        });
+        let err = run(&invocation, &JsonValue::Null).expect_err("strict failure");
+        let output = err.to_output("en");
+        assert_eq!(output["error"]["kind"], "missing_scope");
+        assert_eq!(
+            output["error"]["details"]["paths"],
+            json!(["payload.name", "payload.title"])
+        );
+    }
+
+    #[test]
+    fn lenient_mode_reports_missing_paths_as_warnings() {
+        let invocation = json!({
+            "templates": { "text": "Hi {{payload.name}}!", "strict": false },
+            "payload": {}
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(output["text"], "Hi !");
+        assert_eq!(
+            output["warnings"],
+            json!([{ "kind": "missing_scope", "path": "payload.name" }])
+        );
+    }
+
+    #[test]
+    fn debug_output_exposes_template_cache_counters() {
+        let invocation = json!({
+            "templates": { "text": "cached {{payload.n}}", "debug": true },
+            "payload": { "n": 1 }
+        });
+        let first = run(&invocation, &JsonValue::Null).expect("first run");
+        let second = run(&invocation, &JsonValue::Null).expect("second run");
+
+        assert_eq!(
+            first["debug"]["template_hash"],
+            cache::template_hash("cached {{payload.n}}")
+        );
+        let hits = |output: &JsonValue| output["debug"]["cache"]["hits"].as_u64().expect("hits");
+        assert!(hits(&second) > hits(&first));
+        assert!(second["debug"]["cache"]["size"].as_u64().expect("size") >= 1);
+    }
+
+    #[test]
+    fn wrap_false_emits_bare_string() {
+        let invocation = json!({
+            "templates": { "text": "Hi {{payload.name}}", "output_path": "reply.text", "wrap": false },
+            "payload": { "name": "Ada" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(output, json!("Hi Ada"));
+    }
+
+    #[test]
+    fn wrap_false_with_routes_is_invalid_input() {
+        let invocation = json!({
+            "templates": {
+                "text": "hi",
+                "wrap": false,
+                "routes": [{ "when": "payload.vip", "to": "vip" }]
+            },
+            "payload": { "vip": true }
+        });
+        let err = run(&invocation, &JsonValue::Null).expect_err("error");
+        assert_eq!(err.kind(), "invalid_input");
+    }
+
+    #[test]
+    fn renders_the_pack_smoke_payload() {
+        // Invocation shape sent by ci/component_pack_smoke.sh.
+        let invocation = json!({
+            "config": {
+                "templates": { "text": "helo: {{payload.seed}}", "output_path": "text", "wrap": true }
+            },
+            "msg": { "id": "msg-1", "channel": "chat", "text": "hello", "metadata": {} },
+            "payload": { "seed": "input" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(output["text"], "helo: input");
+    }
+
+    #[test]
+    fn wrap_false_accepts_the_empty_routing_config_flows_emit() {
+        // Shape written by flows/custom.ygtc when no routing was chosen.
+        let invocation = json!({
+            "templates": {
+                "text": "Hi {{payload.name}}",
+                "output_path": "text",
+                "wrap": false,
+                "routing": ""
+            },
+            "payload": { "name": "Ada" }
+        });
+        let output = run(&invocation, &JsonValue::Null).expect("run");
+        assert_eq!(output, json!("Hi Ada"));
+    }
+
+    #[test]
+    fn deterministic_without_seed_or_message_id_is_invalid_input() {
+        let templates = json!({ "text": "{{uuid}}", "deterministic": true });
+        let err = run(
+            &json!({ "templates": templates, "msg": {} }),
+            &JsonValue::Null,
+        )
+        .expect_err("error");
+        assert_eq!(err.kind(), "invalid_input");
+
+        let replay = || {
+            let invocation = json!({ "templates": templates, "msg": { "id": "m-1" } });
+            run(&invocation, &JsonValue::Null).expect("run")["text"].clone()
+        };
+        assert_eq!(replay(), replay());
+    }
+
+    #[test]
+    fn wrap_false_in_lenient_mode_is_invalid_input() {
+        let invocation = json!({
+            "templates": { "text": "hi {{payload.name}}", "wrap": false, "strict": false }
+        });
+        let err = run(&invocation, &JsonValue::Null).expect_err("error");
+        assert_eq!(err.kind(), "invalid_input");
+    }
+
+    #[test]
+    fn invalid_output_path_is_invalid_input() {
+        let invocation = json!({ "templates": { "text": "Hi", "output_path": "reply..text" } });
+        let err = run(&invocation, &JsonValue::Null).expect_err("error");
+        assert_eq!(err.kind(), "invalid_input");
+    }
+
+    #[test]
+    fn dispatches_on_operation_name() {
+        let invocation = json!({
+            "operation": "handle_message",
+            "templates": { "text": "Hi {{payload.name}}" },
+            "payload": { "name": "Ada" }
+        });
+        assert_eq!(
+            run(&invocation, &JsonValue::Null).expect("run")["text"],
+            "Hi Ada"
+        );
+
+        let legacy = json!({ "op": "handle_message", "input": " ping " });
+        let output = run(&legacy, &JsonValue::Null).expect("legacy run");
+        assert_eq!(
+            output["message"],
+            "component-templates::handle_message => ping"
+        );
+    }
+
+    #[test]
+    fn unknown_operation_is_rejected() {
+        let err = run(&json!({ "operation": "shout" }), &JsonValue::Null).expect_err("error");
+        assert_eq!(err, RunError::UnsupportedOperation("shout".to_string()));
+        assert!(err.to_string().contains("`text`, `handle_message`"));
+    }
+
+    #[test]
+    fn error_envelope_is_localized_for_message_locale() {
+        let invocation = json!({
+            "operation": "shout",
+            "msg": { "metadata": { "locale": "fr-FR" } }
+        });
+        let output = run_to_output(&invocation, &JsonValue::Null);
+        let error = &output["error"];
+        assert_eq!(error["code"], "unsupported_operation");
+        assert_eq!(error["key"], "errors.unsupported_operation");
+        assert_eq!(error["args"]["operation"], "shout");
+        assert_eq!(error["args"]["supported"], "text`, `handle_message");
+        assert_eq!(
+            error["message"],
+            "l'opération `shout` n'est pas prise en charge ; utilisez `text`, `handle_message`"
+        );
+    }
+
+    #[test]
+    fn syntax_errors_keep_render_message_with_specific_code() {
+        let invocation = json!({ "templates": { "text": "{{#if}}" } });
+        let error = &run_to_output(&invocation, &JsonValue::Null)["error"];
+        assert_eq!(error["kind"], "template_render");
+        assert_eq!(error["code"], "template_syntax");
+        assert!(
+            error["message"]
+                .as_str()
+                .expect("message")
+                .starts_with("Template rendering failed: template syntax error")
+        );
+    }
+
+    #[test]
+    fn error_envelope_carries_located_diagnostics() {
+        let invocation = json!({
+            "templates": { "text": "Hi\n{{uper payload.name}}" },
+            "payload": { "name": "Ada" }
+        });
+        let output = run_to_output(&invocation, &JsonValue::Null);
+        assert_eq!(output["control"]["routing"], "error");
+        let error = &output["error"];
+        assert_eq!(error["code"], "unknown_helper");
+        let diagnostic = &error["diagnostics"][0];
+        assert_eq!(diagnostic["line"], 2);
+        assert_eq!(diagnostic["column"], 3);
+        assert_eq!(diagnostic["span"], json!({ "start": 5, "end": 9 }));
+        assert_eq!(diagnostic["token"], "uper");
+        assert_eq!(
+            diagnostic["message"],
+            "Unknown helper `uper` at line 2, column 3."
+        );
+        assert_eq!(
+            diagnostic["snippet"],
+            "2 | {{uper payload.name}}\n  |   ^^^^"
+        );
+    }
+
+    #[test]
+    fn helper_argument_errors_report_usage() {
+        let invocation = json!({
+            "templates": { "text": "{{truncate payload.name}}" },
+            "payload": { "name": "Ada" }
+        });
+        let error = &run_to_output(&invocation, &JsonValue::Null)["error"];
+        assert_eq!(error["kind"], "template_render");
+        assert_eq!(error["code"], "helper_arguments");
+        let diagnostic = &error["diagnostics"][0];
+        assert_eq!(diagnostic["token"], "truncate");
+        assert_eq!(
+            diagnostic["fix"]["message"],
+            "Usage: `{{truncate text length [ellipsis]}}`."
+        );
+    }
+
+    #[test]
+    fn missing_template_text_is_invalid_input() {
+        let err = run(&json!({ "payload": {} }), &JsonValue::Null).expect_err("error");
+        assert_eq!(err.kind(), "invalid_input");
+        assert_eq!(err.to_output("en")["error"]["kind"], "invalid_input");
+    }
+}
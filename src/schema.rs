@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+
+use crate::config::{self, DEFAULT_OUTPUT_PATH};
+
+// SchemaIr builders backing `describe` and the component-schema exports.
+// Keep these aligned with schemas/component.schema.json and schemas/io/*.
+
+pub fn string() -> SchemaIr {
+    SchemaIr::String {
+        min_len: Some(0),
+        max_len: None,
+        regex: None,
+        format: None,
+    }
+}
+
+fn open_object() -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::new(),
+        required: Vec::new(),
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+// Object with arbitrary keys whose values all match `value`.
+fn map_of(value: SchemaIr) -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::new(),
+        required: Vec::new(),
+        additional: AdditionalProperties::Schema(Box::new(value)),
+    }
+}
+
+fn scalar_or_object_variants() -> Vec<SchemaIr> {
+    vec![
+        open_object(),
+        string(),
+        SchemaIr::Int {
+            min: None,
+            max: None,
+        },
+        SchemaIr::Float {
+            min: None,
+            max: None,
+        },
+        SchemaIr::Bool,
+        SchemaIr::Null,
+    ]
+}
+
+// Any JSON-compatible value, mirroring the `payload` type list in the io schema.
+fn any_value() -> SchemaIr {
+    let mut variants = scalar_or_object_variants();
+    variants.push(SchemaIr::Array {
+        items: Box::new(SchemaIr::OneOf {
+            variants: scalar_or_object_variants(),
+        }),
+        min_items: None,
+        max_items: None,
+    });
+    SchemaIr::OneOf { variants }
+}
+
+// Node config: `{ templates: { text, ... } }`.
+pub fn config_schema() -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::from([(
+            "templates".to_string(),
+            SchemaIr::Object {
+                properties: BTreeMap::from([
+                    ("text".to_string(), string()),
+                    ("text_i18n".to_string(), map_of(string())),
+                    ("output_path".to_string(), string()),
+                    ("wrap".to_string(), SchemaIr::Bool),
+                    ("strict".to_string(), SchemaIr::Bool),
+                    ("debug".to_string(), SchemaIr::Bool),
+                    ("routing".to_string(), string()),
+                    ("template_hash".to_string(), string()),
+                    ("messages".to_string(), map_of(map_of(string()))),
+                    ("partials".to_string(), map_of(string())),
+                    ("layouts".to_string(), map_of(string())),
+                    ("seed".to_string(), string()),
+                    ("deterministic".to_string(), SchemaIr::Bool),
+                    (
+                        "routes".to_string(),
+                        SchemaIr::Array {
+                            items: Box::new(route_schema()),
+                            min_items: None,
+                            max_items: None,
+                        },
+                    ),
+                ]),
+                required: vec!["text".to_string()],
+                additional: AdditionalProperties::Allow,
+            },
+        )]),
+        required: Vec::new(),
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+// `templates.routes[]` entry: `{ when: <expression>, to: <port> }`.
+fn route_schema() -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::from([("when".to_string(), string()), ("to".to_string(), string())]),
+        required: vec!["when".to_string(), "to".to_string()],
+        additional: AdditionalProperties::Forbid,
+    }
+}
+
+// `control` block emitted next to wrapped output: `{ routing: <port> }`.
+fn control_schema() -> SchemaIr {
+    required_object("routing", string())
+}
+
+// Lenient-mode `warnings`: `[{ kind, path }]`.
+fn warnings_schema() -> SchemaIr {
+    SchemaIr::Array {
+        items: Box::new(SchemaIr::Object {
+            properties: BTreeMap::from([
+                ("kind".to_string(), string()),
+                ("path".to_string(), string()),
+            ]),
+            required: vec!["kind".to_string()],
+            additional: AdditionalProperties::Allow,
+        }),
+        min_items: None,
+        max_items: None,
+    }
+}
+
+// `text` operation input: the invocation envelope sent by flows and harnesses.
+// Everything here is exposed to templates; see `context::build_context`.
+pub fn text_input_schema() -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::from([
+            ("config".to_string(), config_schema()),
+            ("msg".to_string(), msg_schema()),
+            ("payload".to_string(), any_value()),
+            ("message_id".to_string(), string()),
+            (
+                "attempt".to_string(),
+                SchemaIr::Int {
+                    min: Some(0),
+                    max: None,
+                },
+            ),
+            ("node_id".to_string(), string()),
+            ("flow_id".to_string(), string()),
+        ]),
+        required: Vec::new(),
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+// Channel message envelope. Only the fields the context builder reads are
+// declared; hosts may send more.
+fn msg_schema() -> SchemaIr {
+    let nullable_string = || SchemaIr::OneOf {
+        variants: vec![string(), SchemaIr::Null],
+    };
+    SchemaIr::Object {
+        properties: BTreeMap::from([
+            ("id".to_string(), string()),
+            ("tenant".to_string(), open_object()),
+            ("channel".to_string(), string()),
+            ("session_id".to_string(), nullable_string()),
+            ("user_id".to_string(), nullable_string()),
+            ("locale".to_string(), string()),
+            ("metadata".to_string(), open_object()),
+        ]),
+        required: Vec::new(),
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+// `text` operation output: the rendered string nested under `output_path`,
+// e.g. `reply.body.text` -> `{ reply: { body: { text: <string> } } }` plus the
+// optional `control` and `warnings` blocks, or the bare string when `wrap` is
+// false. Either way a failed run emits `error_output_schema` instead.
+// Invalid paths fall back to the default since `run` rejects them anyway.
+pub fn text_output_schema(output_path: &str, wrap: bool) -> SchemaIr {
+    SchemaIr::OneOf {
+        variants: vec![
+            rendered_output_schema(output_path, wrap),
+            error_output_schema(),
+        ],
+    }
+}
+
+fn rendered_output_schema(output_path: &str, wrap: bool) -> SchemaIr {
+    if !wrap {
+        return string();
+    }
+    let segments = config::path_segments(output_path).unwrap_or_else(|_| vec![DEFAULT_OUTPUT_PATH]);
+    let mut output = segments
+        .iter()
+        .rev()
+        .fold(string(), |inner, segment| required_object(segment, inner));
+    if let SchemaIr::Object { properties, .. } = &mut output {
+        properties.insert("control".to_string(), control_schema());
+        properties.insert("warnings".to_string(), warnings_schema());
+    }
+    output
+}
+
+// Failed run: `{ error: { kind, code, key, message, args, details,
+// diagnostics }, control: { routing: "error" } }`.
+fn error_output_schema() -> SchemaIr {
+    let error = SchemaIr::Object {
+        properties: BTreeMap::from([
+            ("kind".to_string(), string()),
+            ("code".to_string(), string()),
+            ("key".to_string(), string()),
+            ("message".to_string(), string()),
+            ("args".to_string(), map_of(string())),
+            ("details".to_string(), open_object()),
+            (
+                "diagnostics".to_string(),
+                SchemaIr::Array {
+                    items: Box::new(open_object()),
+                    min_items: None,
+                    max_items: None,
+                },
+            ),
+        ]),
+        required: vec!["kind".to_string(), "code".to_string(), "key".to_string()],
+        additional: AdditionalProperties::Allow,
+    };
+    SchemaIr::Object {
+        properties: BTreeMap::from([
+            ("error".to_string(), error),
+            ("control".to_string(), control_schema()),
+        ]),
+        required: vec!["error".to_string(), "control".to_string()],
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+fn required_object(key: &str, value: SchemaIr) -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::from([(key.to_string(), value)]),
+        required: vec![key.to_string()],
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+// Legacy `handle_message` input: `{ input: <string> }`.
+pub fn handle_message_input_schema() -> SchemaIr {
+    required_object("input", string())
+}
+
+// Legacy `handle_message` output: `{ message: <string> }`.
+pub fn handle_message_output_schema() -> SchemaIr {
+    required_object("message", string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_envelope(schema: SchemaIr) -> SchemaIr {
+        let SchemaIr::Object {
+            mut properties,
+            required,
+            additional,
+        } = schema
+        else {
+            unreachable!("output schema is an object");
+        };
+        properties.insert("control".to_string(), control_schema());
+        properties.insert("warnings".to_string(), warnings_schema());
+        SchemaIr::Object {
+            properties,
+            required,
+            additional,
+        }
+    }
+
+    fn or_error(schema: SchemaIr) -> SchemaIr {
+        SchemaIr::OneOf {
+            variants: vec![schema, error_output_schema()],
+        }
+    }
+
+    #[test]
+    fn text_output_schema_follows_output_path() {
+        let expected = with_envelope(required_object(
+            "reply",
+            required_object("body", required_object("text", string())),
+        ));
+        assert_eq!(
+            text_output_schema("reply.body.text", true),
+            or_error(expected)
+        );
+        assert_eq!(
+            text_output_schema(DEFAULT_OUTPUT_PATH, true),
+            or_error(with_envelope(required_object("text", string())))
+        );
+    }
+
+    #[test]
+    fn text_output_schema_is_a_string_when_unwrapped() {
+        assert_eq!(
+            text_output_schema("reply.body.text", false),
+            or_error(string())
+        );
+    }
+
+    #[test]
+    fn error_output_requires_error_and_route() {
+        let SchemaIr::Object { required, .. } = error_output_schema() else {
+            unreachable!("error output is an object");
+        };
+        assert_eq!(required, ["error", "control"]);
+    }
+}